@@ -0,0 +1,65 @@
+//! Variable-length integer parsers for byte-stream grammars.
+//!
+//! These back the `uleb128` and `sleb128` builtins of grammars declared with
+//! `input: bytes;`, and can also be used directly as winnow parsers.
+
+use winnow::error::{ErrMode, ModalResult, ParserError};
+use winnow::stream::{Stream, StreamIsPartial};
+use winnow::token::any;
+use winnow::Parser;
+
+/// Unsigned LEB128, as used by DWARF, WebAssembly and protobuf varints.
+///
+/// Backtracks without consuming input if the value does not fit in a `u64`.
+pub fn uleb128<I, E>(input: &mut I) -> ModalResult<u64, E>
+where
+    I: StreamIsPartial + Stream<Token = u8>,
+    E: ParserError<I>,
+{
+    let start = input.checkpoint();
+    let mut value: u64 = 0;
+    let mut shift = 0;
+    loop {
+        let byte = any::<_, E>.parse_next(input)?;
+        let bits = u64::from(byte & 0x7f);
+        if shift >= 64 || (bits << shift) >> shift != bits {
+            input.reset(&start);
+            return Err(ErrMode::from_input(input));
+        }
+        value |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+/// Signed (two's complement) LEB128.
+///
+/// Backtracks without consuming input if the value does not fit in an `i64`.
+pub fn sleb128<I, E>(input: &mut I) -> ModalResult<i64, E>
+where
+    I: StreamIsPartial + Stream<Token = u8>,
+    E: ParserError<I>,
+{
+    let start = input.checkpoint();
+    let mut value: u64 = 0;
+    let mut shift = 0;
+    loop {
+        let byte = any::<_, E>.parse_next(input)?;
+        // The tenth byte only holds the sign bit, which must agree with its padding.
+        let fits = shift < 63 || (shift == 63 && matches!(byte, 0x00 | 0x7f));
+        if !fits {
+            input.reset(&start);
+            return Err(ErrMode::from_input(input));
+        }
+        value |= u64::from(byte & 0x7f) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            if shift < 64 && byte & 0x40 != 0 {
+                value |= u64::MAX << shift;
+            }
+            return Ok(value as i64);
+        }
+    }
+}
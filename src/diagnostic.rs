@@ -0,0 +1,230 @@
+//! Machine-readable diagnostics for editors and CI.
+//!
+//! A [`Diagnostic`] has the shape of an LSP `Diagnostic`: a zero-based range
+//! in UTF-16 code units, a numeric severity, a source and a message. The
+//! byte range, expected items, rule stack and suggested fixes are carried in
+//! its `data` field. [`Diagnostic::to_json`] serializes it without any
+//! external dependency.
+//!
+//! A generated grammar module for text input with the default
+//! `ContextError` also provides `MyGrammar::diagnostic(&error, source)`,
+//! which fills in the grammar name as the diagnostic source. Grammars with
+//! `input: bytes;` or a custom `error` type have no such function.
+
+use std::fmt::Write;
+
+use winnow::error::{ContextError, ParseError};
+
+use crate::report::Report;
+
+/// How serious a diagnostic is, numbered as in LSP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error = 1,
+    Warning = 2,
+    Information = 3,
+    Hint = 4,
+}
+
+/// A zero-based line and UTF-16 column, as in LSP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub character: usize,
+}
+
+/// A range between two positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// A suggested edit that would fix the error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    pub title: String,
+    pub range: Range,
+    pub new_text: String,
+}
+
+/// A parse error with its location resolved against the source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub range: Range,
+    pub severity: Severity,
+    pub source: String,
+    pub message: String,
+    /// The file name, if the error was reported with one.
+    pub file: Option<String>,
+    /// The byte range of the error in the source.
+    pub span: std::ops::Range<usize>,
+    /// The 1-based line and column, as shown in reports.
+    pub line_col: (usize, usize),
+    pub expected: Vec<String>,
+    /// The rules being parsed, outermost first.
+    pub rules: Vec<String>,
+    pub fixes: Vec<Fix>,
+}
+
+impl Diagnostic {
+    /// Builds a diagnostic for `error`, raised while parsing `source`.
+    pub fn new<I>(error: &ParseError<I, ContextError>, source: &str) -> Self {
+        Self::from(&Report::new(error, source))
+    }
+
+    /// Sets the `source` field, naming the tool or grammar that produced it.
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    /// Sets the file name carried in `data`.
+    pub fn with_file(mut self, file: impl Into<String>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// Serializes the diagnostic as a JSON object.
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        out.push('{');
+        write!(out, "\"range\":{}", range_json(&self.range)).unwrap();
+        write!(out, ",\"severity\":{}", self.severity as u8).unwrap();
+        write!(out, ",\"source\":{}", string_json(&self.source)).unwrap();
+        write!(out, ",\"message\":{}", string_json(&self.message)).unwrap();
+        out.push_str(",\"data\":{");
+        if let Some(file) = &self.file {
+            write!(out, "\"file\":{},", string_json(file)).unwrap();
+        }
+        write!(
+            out,
+            "\"span\":{{\"start\":{},\"end\":{}}}",
+            self.span.start, self.span.end
+        )
+        .unwrap();
+        write!(
+            out,
+            ",\"line\":{},\"column\":{}",
+            self.line_col.0, self.line_col.1
+        )
+        .unwrap();
+        write!(out, ",\"expected\":{}", strings_json(&self.expected)).unwrap();
+        write!(out, ",\"rules\":{}", strings_json(&self.rules)).unwrap();
+        out.push_str(",\"fixes\":[");
+        for (i, fix) in self.fixes.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            write!(
+                out,
+                "{{\"title\":{},\"range\":{},\"newText\":{}}}",
+                string_json(&fix.title),
+                range_json(&fix.range),
+                string_json(&fix.new_text)
+            )
+            .unwrap();
+        }
+        out.push_str("]}}");
+        out
+    }
+}
+
+impl From<&Report<'_>> for Diagnostic {
+    fn from(report: &Report<'_>) -> Self {
+        let source = report.source();
+        let start = report.offset();
+        let end = start + report.found().len();
+        let range = Range {
+            start: position(source, start),
+            end: position(source, end),
+        };
+
+        // A misspelled keyword can be replaced, and a single missing token
+        // can be inserted after the preceding text.
+        let mut fixes = Vec::new();
+        if let Some(keyword) = report.suggestion() {
+            fixes.push(Fix {
+                title: format!("replace with `{}`", keyword),
+                range,
+                new_text: keyword.to_string(),
+            });
+        }
+        if let ([_], [literal]) = (report.expected(), report.literals()) {
+            let at = source[..start].trim_end().len();
+            let at = position(source, at);
+            fixes.push(Fix {
+                title: format!("insert `{}`", literal),
+                range: Range { start: at, end: at },
+                new_text: literal.clone(),
+            });
+        }
+
+        Diagnostic {
+            range,
+            severity: Severity::Error,
+            source: "winnow-grammar".to_string(),
+            message: report.message(),
+            file: report.file().map(String::from),
+            span: start..end,
+            line_col: report.line_col(),
+            expected: report.expected().to_vec(),
+            rules: report.rules().to_vec(),
+            fixes,
+        }
+    }
+}
+
+impl From<Report<'_>> for Diagnostic {
+    fn from(report: Report<'_>) -> Self {
+        Self::from(&report)
+    }
+}
+
+/// The LSP position of a byte offset: zero-based line, UTF-16 column.
+pub fn position(source: &str, offset: usize) -> Position {
+    let before = &source[..offset.min(source.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Position {
+        line: before.matches('\n').count(),
+        character: before[line_start..].encode_utf16().count(),
+    }
+}
+
+fn range_json(range: &Range) -> String {
+    format!(
+        "{{\"start\":{},\"end\":{}}}",
+        position_json(&range.start),
+        position_json(&range.end)
+    )
+}
+
+fn position_json(position: &Position) -> String {
+    format!(
+        "{{\"line\":{},\"character\":{}}}",
+        position.line, position.character
+    )
+}
+
+fn strings_json(items: &[String]) -> String {
+    let items: Vec<String> = items.iter().map(|s| string_json(s)).collect();
+    format!("[{}]", items.join(","))
+}
+
+fn string_json(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,312 @@
+//! Error helpers for generated parsers.
+//!
+//! Literals and builtins attach `StrContext::Expected` entries to their
+//! errors. When every alternative of a rule or group fails, [`Furthest`]
+//! keeps the error that got furthest into the input and merges the
+//! expectations of alternatives that failed at the same position, so the
+//! error lists every token that could have appeared there. Grammars with a
+//! custom `error` type combine such ties with `ParserError::or` instead.
+
+use std::cell::Cell;
+use std::fmt;
+
+use winnow::error::{
+    AddContext, ContextError, ErrMode, FromExternalError, ModalResult, ParseError, ParserError,
+    StrContext, StrContextValue,
+};
+use winnow::stream::{AsChar, Stream};
+use winnow::Parser;
+
+/// Tracks the furthest failure among the alternatives of an `alt`.
+pub struct Furthest<I: Stream, E = ContextError> {
+    best: Option<(usize, I::Checkpoint, E)>,
+}
+
+impl<I: Stream, E> Default for Furthest<I, E> {
+    fn default() -> Self {
+        Self { best: None }
+    }
+}
+
+impl<I: Stream, E: ParserError<I>> Furthest<I, E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the error of an alternative that started at `start` and left
+    /// `input` at the point of failure. Errors of alternatives that failed at
+    /// the same position are combined with [`ParserError::or`].
+    pub fn record(&mut self, input: &I, start: &I::Checkpoint, error: E) {
+        self.record_with(input, start, error, |best, error, _, _| best.or(error));
+    }
+
+    /// Leaves `input` at the furthest failure and returns its error.
+    pub fn finish(self, input: &mut I) -> ErrMode<E> {
+        match self.best {
+            Some((_, checkpoint, error)) => {
+                input.reset(&checkpoint);
+                ErrMode::Backtrack(error)
+            }
+            None => ErrMode::Backtrack(E::from_input(input)),
+        }
+    }
+
+    fn record_with(
+        &mut self,
+        input: &I,
+        start: &I::Checkpoint,
+        error: E,
+        merge: impl FnOnce(E, E, &I, &I::Checkpoint) -> E,
+    ) {
+        let offset = input.offset_from(start);
+        match self.best.take() {
+            Some(best) if best.0 > offset => self.best = Some(best),
+            Some((best, checkpoint, merged)) if best == offset => {
+                let merged = merge(merged, error, input, &checkpoint);
+                self.best = Some((best, checkpoint, merged));
+            }
+            _ => self.best = Some((offset, input.checkpoint(), error)),
+        }
+    }
+}
+
+impl<I: Stream> Furthest<I, ContextError> {
+    /// Like [`Furthest::record`], but ties keep the expectations of every
+    /// alternative, so the error lists each token that could have appeared.
+    pub fn record_merged(&mut self, input: &I, start: &I::Checkpoint, error: ContextError) {
+        self.record_with(
+            input,
+            start,
+            error,
+            |mut merged, error, input, checkpoint| {
+                for context in error.context() {
+                    if !merged.context().any(|c| c == context) {
+                        merged = merged.add_context(input, checkpoint, context.clone());
+                    }
+                }
+                merged
+            },
+        );
+    }
+}
+
+/// Runs `parser`, reporting a failure at its first token as expecting
+/// `label` instead of the tokens it tried. Failures further in, and cut
+/// errors, are passed through. `ws` is the grammar's whitespace parser.
+pub fn expect<I, O, E>(
+    input: &mut I,
+    label: &'static str,
+    mut ws: impl Parser<I, (), E>,
+    mut parser: impl Parser<I, O, E>,
+) -> ModalResult<O, E>
+where
+    I: Stream,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    let start = input.checkpoint();
+    match parser.parse_next(input) {
+        Err(ErrMode::Backtrack(error)) => {
+            let failed = input.checkpoint();
+            let progress = input.offset_from(&start);
+            input.reset(&start);
+            let _ = ws.parse_next(input);
+            if progress > input.offset_from(&start) {
+                input.reset(&failed);
+                return Err(ErrMode::Backtrack(error));
+            }
+            let at = input.checkpoint();
+            Err(ErrMode::Backtrack(E::from_input(input).add_context(
+                input,
+                &at,
+                StrContext::Expected(StrContextValue::Description(label)),
+            )))
+        }
+        result => result,
+    }
+}
+
+/// Runs `parser`, turning a cut error raised by one of its own cuts into a
+/// backtrack. Groups, options and repetitions containing a cut are wrapped
+/// in it, so the cut commits only to their own alternatives or iteration
+/// and not to the enclosing ones. Their cuts go through [`commit`], which
+/// sets `scope`; cut errors from the rules they call are passed on.
+pub fn uncut<'a, I, O, E>(
+    scope: &'a Cell<bool>,
+    mut parser: impl Parser<I, O, E> + 'a,
+) -> impl Parser<I, O, E> + 'a
+where
+    I: Stream + 'a,
+    O: 'a,
+    E: 'a,
+{
+    move |input: &mut I| {
+        scope.set(false);
+        match parser.parse_next(input) {
+            Err(ErrMode::Cut(error)) if scope.get() => Err(ErrMode::Backtrack(error)),
+            result => result,
+        }
+    }
+}
+
+/// `cut_err` for a cut inside an [`uncut`]: records in `scope` that the
+/// cut error came from this cut.
+pub fn commit<'a, I, O, E>(
+    scope: &'a Cell<bool>,
+    mut parser: impl Parser<I, O, E> + 'a,
+) -> impl Parser<I, O, E> + 'a
+where
+    I: Stream + 'a,
+    O: 'a,
+    E: 'a,
+{
+    move |input: &mut I| match parser.parse_next(input) {
+        Err(ErrMode::Backtrack(error)) => {
+            scope.set(true);
+            Err(ErrMode::Cut(error))
+        }
+        result => result,
+    }
+}
+
+/// A "did you mean" hint, attached to an error as its cause.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Suggestion(pub &'static str);
+
+impl fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "did you mean `{}`?", self.0)
+    }
+}
+
+impl std::error::Error for Suggestion {}
+
+/// Attaches a [`Suggestion`] to the error of alternatives that started at
+/// `start`, when they failed on their first word and that word is a likely
+/// misspelling of one of their leading `keywords`.
+#[allow(deprecated)] // `FromExternalError` still takes an `ErrorKind`
+pub fn suggest<I>(
+    input: &mut I,
+    start: &I::Checkpoint,
+    error: ErrMode<ContextError>,
+    keywords: &[&'static str],
+) -> ErrMode<ContextError>
+where
+    I: Stream,
+    I::Token: AsChar,
+{
+    let ErrMode::Backtrack(error) = error else {
+        return error;
+    };
+    let failed = input.checkpoint();
+    let progress = input.offset_from(start);
+
+    input.reset(start);
+    let mut word = String::new();
+    let mut word_start = None;
+    for (offset, token) in input.iter_offsets() {
+        let c = token.as_char();
+        if c.is_alphanumeric() || c == '_' {
+            word_start.get_or_insert(offset);
+            word.push(c);
+        } else if word_start.is_some() || !c.is_whitespace() {
+            break;
+        }
+    }
+    input.reset(&failed);
+
+    let lower = word.to_lowercase();
+    // On a tie, prefer a keyword with the same first letter.
+    let best = keywords
+        .iter()
+        .map(|&keyword| (edit_distance(&lower, keyword), keyword))
+        .filter(|&(distance, keyword)| {
+            distance <= (keyword.chars().count() / 3).max(1) && distance < word.chars().count()
+        })
+        .min_by_key(|&(distance, keyword)| {
+            (distance, keyword.chars().next() != lower.chars().next())
+        });
+    match best {
+        Some((_, keyword)) if word_start == Some(progress) && word != keyword => {
+            let mut hinted = ContextError::from_external_error(
+                input,
+                winnow::error::ErrorKind::Verify,
+                Suggestion(keyword),
+            );
+            for context in error.context() {
+                hinted = hinted.add_context(input, &failed, context.clone());
+            }
+            ErrMode::Backtrack(hinted)
+        }
+        _ => ErrMode::Backtrack(error),
+    }
+}
+
+/// The keyword suggested by [`suggest`], if any. The [`Suggestion`] is the
+/// cause of the error, which `ContextError::clone` turns into plain text, so
+/// read it from the original error rather than a clone.
+pub fn suggestion(error: &ContextError) -> Option<String> {
+    let Suggestion(keyword) = error.cause()?.downcast_ref::<Suggestion>()?;
+    Some(keyword.to_string())
+}
+
+/// The number of single-character insertions, deletions, substitutions and
+/// adjacent transpositions turning `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // Rows for `a[..i-1]`, `a[..i]` and `a[..i+1]` against every prefix of `b`.
+    let mut before: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 0..a.len() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for j in 0..b.len() {
+            let cost = usize::from(a[i] != b[j]);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+            if i > 0 && j > 0 && a[i] == b[j - 1] && a[i - 1] == b[j] {
+                current[j + 1] = current[j + 1].min(before[j - 1] + 1);
+            }
+        }
+        before = std::mem::replace(&mut previous, current);
+    }
+    previous[b.len()]
+}
+
+/// The tokens an error expected, deduplicated, in the order they were tried.
+pub fn expected(error: &ContextError) -> Vec<String> {
+    let mut items: Vec<String> = Vec::new();
+    for context in error.context() {
+        if let StrContext::Expected(value) = context {
+            let item = value.to_string();
+            if !items.contains(&item) {
+                items.push(item);
+            }
+        }
+    }
+    items
+}
+
+/// Describes what was expected: "expected `)`" or "expected one of `+`, `-`, `)`".
+pub fn describe(error: &ContextError) -> String {
+    match expected(error).as_slice() {
+        [] => "unexpected input".to_string(),
+        [only] => format!("expected {}", only),
+        items => format!("expected one of {}", items.join(", ")),
+    }
+}
+
+/// The 1-based line and column (in characters) of a byte offset in `source`.
+pub fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// A one-line message for a failed parse of `source`, such as
+/// "expected one of `+`, `-`, `)` at 1:7".
+pub fn message<I>(error: &ParseError<I, ContextError>, source: &str) -> String {
+    let (line, col) = line_col(source, error.offset());
+    format!("{} at {}:{}", describe(error.inner()), line, col)
+}
@@ -0,0 +1,478 @@
+//! Strict floating point parsers for specific number syntaxes.
+//!
+//! The plain `f32`/`f64` builtins use `winnow::ascii::float`, which also
+//! accepts forms like `inf`, `NaN` or `1.` in any case. The parsers here
+//! accept exactly one dialect each and back the `json_f64`, `toml_f64`,
+//! `rust_f64` and `hex_f64` builtins (and their `f32` counterparts), plus
+//! `json_decimal`, which returns a JSON number as written.
+//!
+//! A well-formed number too large for its type fails with a cut error at its
+//! first character instead of becoming infinity.
+
+use winnow::error::{AddContext, ErrMode, ModalResult, ParserError, StrContext, StrContextValue};
+use winnow::stream::{AsBStr, AsChar, Stream, StreamIsPartial};
+
+/// A floating point type that numbers can be parsed into.
+pub trait Float: Sized + Copy {
+    /// The type name, also used as the Rust literal suffix.
+    const NAME: &'static str;
+    /// The expectation reported when a number is out of range.
+    const RANGE: &'static str;
+
+    /// Parses decimal text without separators, as accepted by `str::parse`.
+    fn from_decimal(text: &str) -> Option<Self>;
+
+    /// Computes `mantissa * 2^exp`.
+    fn from_binary(mantissa: u64, exp: i32) -> Self;
+
+    fn from_f64(value: f64) -> Self;
+
+    fn negate(self) -> Self;
+
+    fn is_finite(self) -> bool;
+}
+
+macro_rules! impl_float {
+    ($($ty:ident),*) => {
+        $(
+            impl Float for $ty {
+                const NAME: &'static str = stringify!($ty);
+                const RANGE: &'static str = concat!("a value that fits in `", stringify!($ty), "`");
+
+                fn from_decimal(text: &str) -> Option<Self> {
+                    text.parse().ok()
+                }
+
+                fn from_binary(mantissa: u64, exp: i32) -> Self {
+                    // Scale in steps so intermediate powers of two stay representable.
+                    let mut value = mantissa as f64;
+                    let mut exp = exp;
+                    while exp != 0 {
+                        let step = exp.clamp(-1000, 1000);
+                        value *= 2f64.powi(step);
+                        exp -= step;
+                    }
+                    value as $ty
+                }
+
+                fn from_f64(value: f64) -> Self {
+                    value as $ty
+                }
+
+                fn negate(self) -> Self {
+                    -self
+                }
+
+                fn is_finite(self) -> bool {
+                    $ty::is_finite(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_float!(f32, f64);
+
+/// A JSON (RFC 8259) number: no leading `+`, no leading zeros, no `inf` or `NaN`.
+pub fn json<I, T, E>(input: &mut I) -> ModalResult<T, E>
+where
+    I: StreamIsPartial + Stream,
+    I::Token: AsChar,
+    I::Slice: AsBStr,
+    T: Float,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    number(input, scan_json)
+}
+
+/// A TOML float: a fraction or exponent is required, `_` may separate
+/// digits, and `inf`/`nan` are accepted in lowercase with an optional sign.
+pub fn toml<I, T, E>(input: &mut I) -> ModalResult<T, E>
+where
+    I: StreamIsPartial + Stream,
+    I::Token: AsChar,
+    I::Slice: AsBStr,
+    T: Float,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    number(input, scan_toml)
+}
+
+/// A Rust float literal with an optional leading `-`. A fraction, exponent
+/// or suffix is required, and a suffix must name the target type.
+pub fn rust<I, T, E>(input: &mut I) -> ModalResult<T, E>
+where
+    I: StreamIsPartial + Stream,
+    I::Token: AsChar,
+    I::Slice: AsBStr,
+    T: Float,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    number(input, |text| {
+        let scanned = scan_rust(text)?;
+        match scanned.suffix {
+            Some(suffix) if suffix != T::NAME => Some(Scanned {
+                value: Value::WrongSuffix(scanned.len - suffix.len()),
+                ..scanned
+            }),
+            _ => Some(scanned),
+        }
+    })
+}
+
+/// A C99 hexadecimal float such as `0x1.8p3`. The binary exponent is required.
+pub fn hex<I, T, E>(input: &mut I) -> ModalResult<T, E>
+where
+    I: StreamIsPartial + Stream,
+    I::Token: AsChar,
+    I::Slice: AsBStr,
+    T: Float,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    number(input, scan_hex)
+}
+
+/// A JSON number returned exactly as written, for arbitrary precision types.
+pub fn decimal<I, E>(input: &mut I) -> ModalResult<String, E>
+where
+    I: StreamIsPartial + Stream,
+    I::Token: AsChar,
+    I::Slice: AsBStr,
+    E: ParserError<I>,
+{
+    let start = input.checkpoint();
+    let candidate = candidate(input);
+    input.reset(&start);
+    let text = std::str::from_utf8(candidate.as_bstr()).unwrap_or_default();
+    match scan_json(text) {
+        Some(scanned) => {
+            input.next_slice(scanned.len);
+            Ok(text[..scanned.len].to_string())
+        }
+        None => Err(ErrMode::from_input(input)),
+    }
+}
+
+/// The result of scanning a number, before conversion.
+struct Scanned {
+    /// Length of the number in the input.
+    len: usize,
+    value: Value,
+    suffix: Option<&'static str>,
+}
+
+enum Value {
+    /// Decimal text accepted by `str::parse`.
+    Decimal(String),
+    /// `mantissa * 2^exp`, negated if the flag is set.
+    Binary(bool, u64, i32),
+    /// A special value that is not subject to the range check.
+    Special(f64),
+    /// A Rust suffix naming another type, at the given offset.
+    WrongSuffix(usize),
+}
+
+fn number<I, T, E>(input: &mut I, scan: impl Fn(&str) -> Option<Scanned>) -> ModalResult<T, E>
+where
+    I: StreamIsPartial + Stream,
+    I::Token: AsChar,
+    I::Slice: AsBStr,
+    T: Float,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    let start = input.checkpoint();
+    let candidate = candidate(input);
+    input.reset(&start);
+    let text = std::str::from_utf8(candidate.as_bstr()).unwrap_or_default();
+
+    let Some(scanned) = scan(text) else {
+        return Err(ErrMode::from_input(input));
+    };
+    let value = match scanned.value {
+        Value::Decimal(digits) => T::from_decimal(&digits),
+        Value::Binary(negative, mantissa, exp) => {
+            let value = T::from_binary(mantissa, exp);
+            Some(if negative { value.negate() } else { value })
+        }
+        Value::Special(value) => {
+            input.next_slice(scanned.len);
+            return Ok(T::from_f64(value));
+        }
+        Value::WrongSuffix(offset) => {
+            input.next_slice(offset);
+            let suffix_start = input.checkpoint();
+            return Err(expected(input, &suffix_start, T::NAME));
+        }
+    };
+
+    match value.filter(|v| v.is_finite()) {
+        Some(value) => {
+            input.next_slice(scanned.len);
+            Ok(value)
+        }
+        None => Err(expected(input, &start, T::RANGE)),
+    }
+}
+
+/// A cut error at the current position: the number is malformed, not absent.
+fn expected<I, E>(input: &I, start: &I::Checkpoint, what: &'static str) -> ErrMode<E>
+where
+    I: Stream,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    ErrMode::Cut(E::from_input(input).add_context(
+        input,
+        start,
+        StrContext::Expected(StrContextValue::Description(what)),
+    ))
+}
+
+/// Consumes the longest run of characters that may belong to a number.
+/// The caller resets the input and consumes only what the dialect accepts.
+fn candidate<I>(input: &mut I) -> I::Slice
+where
+    I: Stream,
+    I::Token: AsChar,
+{
+    let len = input
+        .offset_for(|t| {
+            let c = t.as_char();
+            !(c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '+' | '-'))
+        })
+        .unwrap_or_else(|| input.eof_offset());
+    input.next_slice(len)
+}
+
+/// Byte-level cursor over the candidate text.
+struct Cursor<'a> {
+    text: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text: text.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, ahead: usize) -> Option<u8> {
+        self.text.get(self.pos + ahead).copied()
+    }
+
+    fn eat(&mut self, pred: impl Fn(u8) -> bool) -> bool {
+        match self.peek() {
+            Some(b) if pred(b) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        if self.text[self.pos..].starts_with(s.as_bytes()) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Digits with single `_` separators between them; at least one digit.
+    fn separated_digits(&mut self) -> bool {
+        if !self.eat(|b| b.is_ascii_digit()) {
+            return false;
+        }
+        loop {
+            if self.peek() == Some(b'_') && self.peek_at(1).is_some_and(|b| b.is_ascii_digit()) {
+                self.pos += 1;
+            }
+            if !self.eat(|b| b.is_ascii_digit()) {
+                return true;
+            }
+        }
+    }
+
+    /// The text consumed since `start`, without `_` separators.
+    fn digits_since(&self, start: usize) -> String {
+        self.text[start..self.pos]
+            .iter()
+            .filter(|&&b| b != b'_')
+            .map(|&b| b as char)
+            .collect()
+    }
+}
+
+fn scan_json(text: &str) -> Option<Scanned> {
+    let mut c = Cursor::new(text);
+    c.eat(|b| b == b'-');
+    if !c.eat(|b| b == b'0') {
+        if !c.eat(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        while c.eat(|b| b.is_ascii_digit()) {}
+    }
+    if c.peek() == Some(b'.') && c.peek_at(1).is_some_and(|b| b.is_ascii_digit()) {
+        c.pos += 1;
+        while c.eat(|b| b.is_ascii_digit()) {}
+    }
+    scan_exponent(&mut c, |c| {
+        let start = c.pos;
+        while c.eat(|b| b.is_ascii_digit()) {}
+        c.pos > start
+    });
+    Some(Scanned {
+        len: c.pos,
+        value: Value::Decimal(c.digits_since(0)),
+        suffix: None,
+    })
+}
+
+fn scan_toml(text: &str) -> Option<Scanned> {
+    let mut c = Cursor::new(text);
+    let negative = c.peek() == Some(b'-');
+    c.eat(|b| b == b'+' || b == b'-');
+    for (name, value) in [("inf", f64::INFINITY), ("nan", f64::NAN)] {
+        if c.eat_str(name) {
+            let value = if negative { -value } else { value };
+            return Some(Scanned {
+                len: c.pos,
+                value: Value::Special(value),
+                suffix: None,
+            });
+        }
+    }
+
+    if !c.eat(|b| b == b'0') && !c.separated_digits() {
+        return None;
+    }
+    let mut is_float = false;
+    if c.peek() == Some(b'.') {
+        c.pos += 1;
+        if !c.separated_digits() {
+            return None;
+        }
+        is_float = true;
+    }
+    is_float |= scan_exponent(&mut c, |c| c.separated_digits());
+    is_float.then(|| Scanned {
+        len: c.pos,
+        value: Value::Decimal(c.digits_since(0)),
+        suffix: None,
+    })
+}
+
+fn scan_rust(text: &str) -> Option<Scanned> {
+    let mut c = Cursor::new(text);
+    c.eat(|b| b == b'-');
+    if !c.eat(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    while c.eat(|b| b.is_ascii_digit() || b == b'_') {}
+
+    let mut is_float = false;
+    // `1.` is a float, but `1..2`, `1._x` and `1.max(2)` are not.
+    if c.peek() == Some(b'.')
+        && !c
+            .peek_at(1)
+            .is_some_and(|b| b == b'.' || b == b'_' || b.is_ascii_alphabetic())
+    {
+        c.pos += 1;
+        if c.eat(|b| b.is_ascii_digit()) {
+            while c.eat(|b| b.is_ascii_digit() || b == b'_') {}
+        }
+        is_float = true;
+    }
+    is_float |= scan_exponent(&mut c, |c| {
+        let start = c.pos;
+        while c.eat(|b| b.is_ascii_digit() || b == b'_') {}
+        c.text[start..c.pos].iter().any(u8::is_ascii_digit)
+    });
+
+    let digits = c.digits_since(0);
+    let suffix = ["f32", "f64"].into_iter().find(|s| c.eat_str(s));
+    (is_float || suffix.is_some()).then_some(Scanned {
+        len: c.pos,
+        value: Value::Decimal(digits),
+        suffix,
+    })
+}
+
+fn scan_hex(text: &str) -> Option<Scanned> {
+    let mut c = Cursor::new(text);
+    let negative = c.peek() == Some(b'-');
+    c.eat(|b| b == b'+' || b == b'-');
+    if !(c.eat_str("0x") || c.eat_str("0X")) {
+        return None;
+    }
+
+    let mut mantissa: u64 = 0;
+    let mut exp: i32 = 0;
+    let mut any_digit = false;
+    let mut sticky = false;
+    let mut fraction = false;
+    loop {
+        if !fraction && c.eat(|b| b == b'.') {
+            fraction = true;
+            continue;
+        }
+        let Some(digit) = c.peek().and_then(|b| (b as char).to_digit(16)) else {
+            break;
+        };
+        c.pos += 1;
+        any_digit = true;
+        if mantissa >> 60 == 0 {
+            mantissa = mantissa << 4 | u64::from(digit);
+            if fraction {
+                exp -= 4;
+            }
+        } else {
+            // Beyond 60 significant bits only rounding matters.
+            sticky |= digit != 0;
+            if !fraction {
+                exp += 4;
+            }
+        }
+    }
+    if !any_digit || !c.eat(|b| b == b'p' || b == b'P') {
+        return None;
+    }
+    let exp_negative = c.peek() == Some(b'-');
+    c.eat(|b| b == b'+' || b == b'-');
+    let start = c.pos;
+    while c.eat(|b| b.is_ascii_digit()) {}
+    if c.pos == start {
+        return None;
+    }
+    let written: i32 = c.digits_since(start).parse().unwrap_or(i32::MAX);
+    c.eat(|b| matches!(b, b'f' | b'F' | b'l' | b'L'));
+
+    // Exponents beyond the range of any float saturate to zero or infinity.
+    let exp = exp
+        .saturating_add(if exp_negative { -written } else { written })
+        .clamp(-2000, 2000);
+    Some(Scanned {
+        len: c.pos,
+        value: Value::Binary(negative, mantissa | u64::from(sticky), exp),
+        suffix: None,
+    })
+}
+
+/// `[eE][+-]?digits`, leaving the cursor untouched if no digits follow.
+fn scan_exponent(c: &mut Cursor, digits: impl Fn(&mut Cursor) -> bool) -> bool {
+    let save = c.pos;
+    if c.eat(|b| b == b'e' || b == b'E') {
+        c.eat(|b| b == b'+' || b == b'-');
+        if digits(c) {
+            return true;
+        }
+    }
+    c.pos = save;
+    false
+}
@@ -0,0 +1,187 @@
+//! Typed integer parsers for radix-prefixed and Rust-style literals.
+//!
+//! These back the `hex_u32`, `oct_u8`, `bin_u16` (and similar) builtins and
+//! `int_literal::<T>`. All of them accept `_` digit separators and an optional
+//! type suffix naming the target type (`0xffu8`). A literal that does not fit
+//! its type fails with a cut error positioned at its first character.
+
+use winnow::error::{AddContext, ErrMode, ModalResult, ParserError, StrContext, StrContextValue};
+use winnow::stream::{AsBStr, AsChar, Compare, CompareResult, Stream, StreamIsPartial};
+
+/// An integer type that literals can be parsed into.
+pub trait Integer: Sized {
+    /// The type name, also used as the literal suffix.
+    const NAME: &'static str;
+    const SIGNED: bool;
+    /// The expectation reported when a literal is out of range.
+    const RANGE: &'static str;
+
+    /// Parses `digits` (without separators) in `radix`, `None` on overflow.
+    fn from_digits(digits: &str, radix: u32) -> Option<Self>;
+}
+
+macro_rules! impl_integer {
+    ($($ty:ident: $signed:expr),* $(,)?) => {
+        $(
+            impl Integer for $ty {
+                const NAME: &'static str = stringify!($ty);
+                const SIGNED: bool = $signed;
+                const RANGE: &'static str = concat!("a value that fits in `", stringify!($ty), "`");
+
+                fn from_digits(digits: &str, radix: u32) -> Option<Self> {
+                    $ty::from_str_radix(digits, radix).ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_integer! {
+    u8: false, u16: false, u32: false, u64: false, u128: false, usize: false,
+    i8: true, i16: true, i32: true, i64: true, i128: true, isize: true,
+}
+
+const SUFFIXES: [&str; 12] = [
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+];
+
+/// A hexadecimal integer with an optional `0x` prefix.
+pub fn hex<I, T, E>(input: &mut I) -> ModalResult<T, E>
+where
+    I: StreamIsPartial + Stream + for<'a> Compare<&'a str>,
+    I::Token: AsChar,
+    I::Slice: AsBStr,
+    T: Integer,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    integer(input, Some(16))
+}
+
+/// An octal integer with an optional `0o` prefix.
+pub fn oct<I, T, E>(input: &mut I) -> ModalResult<T, E>
+where
+    I: StreamIsPartial + Stream + for<'a> Compare<&'a str>,
+    I::Token: AsChar,
+    I::Slice: AsBStr,
+    T: Integer,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    integer(input, Some(8))
+}
+
+/// A binary integer with an optional `0b` prefix.
+pub fn bin<I, T, E>(input: &mut I) -> ModalResult<T, E>
+where
+    I: StreamIsPartial + Stream + for<'a> Compare<&'a str>,
+    I::Token: AsChar,
+    I::Slice: AsBStr,
+    T: Integer,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    integer(input, Some(2))
+}
+
+/// A Rust-style integer literal: decimal, or `0x`/`0o`/`0b` prefixed.
+/// Signed types also accept a leading `-`.
+pub fn int_literal<I, T, E>(input: &mut I) -> ModalResult<T, E>
+where
+    I: StreamIsPartial + Stream + for<'a> Compare<&'a str>,
+    I::Token: AsChar,
+    I::Slice: AsBStr,
+    T: Integer,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    integer(input, None)
+}
+
+/// Shared implementation. With `radix: None` the prefix selects the radix.
+fn integer<I, T, E>(input: &mut I, radix: Option<u32>) -> ModalResult<T, E>
+where
+    I: StreamIsPartial + Stream + for<'a> Compare<&'a str>,
+    I::Token: AsChar,
+    I::Slice: AsBStr,
+    T: Integer,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    let start = input.checkpoint();
+
+    let negative = T::SIGNED && radix.is_none() && eat(input, "-");
+    let (radix, prefixed) = match radix {
+        Some(radix) => {
+            let prefix = match radix {
+                16 => "0x",
+                8 => "0o",
+                _ => "0b",
+            };
+            (radix, eat(input, prefix))
+        }
+        None if eat(input, "0x") => (16, true),
+        None if eat(input, "0o") => (8, true),
+        None if eat(input, "0b") => (2, true),
+        None => (10, false),
+    };
+
+    let len = input
+        .offset_for(|t| {
+            let c = t.as_char();
+            !(c.is_digit(radix) || c == '_')
+        })
+        .unwrap_or_else(|| input.eof_offset());
+    let text = input.next_slice(len);
+    let text = std::str::from_utf8(text.as_bstr()).unwrap_or_default();
+
+    // `_1` is not a number, but `0x_1` is.
+    if (text.starts_with('_') && !prefixed) || text.chars().all(|c| c == '_') {
+        input.reset(&start);
+        return Err(ErrMode::from_input(input));
+    }
+    let mut digits = String::with_capacity(text.len() + 1);
+    if negative {
+        digits.push('-');
+    }
+    digits.extend(text.chars().filter(|&c| c != '_'));
+
+    // A suffix must name the target type.
+    let suffix_start = input.checkpoint();
+    if let Some(suffix) = SUFFIXES.iter().find(|s| eat(input, s)) {
+        if *suffix != T::NAME {
+            input.reset(&suffix_start);
+            return Err(expected(input, &suffix_start, T::NAME));
+        }
+    }
+
+    match T::from_digits(&digits, radix) {
+        Some(value) => Ok(value),
+        None => {
+            input.reset(&start);
+            Err(expected(input, &start, T::RANGE))
+        }
+    }
+}
+
+/// A cut error at the current position: the literal is malformed, not absent.
+fn expected<I, E>(input: &I, start: &I::Checkpoint, what: &'static str) -> ErrMode<E>
+where
+    I: Stream,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    ErrMode::Cut(E::from_input(input).add_context(
+        input,
+        start,
+        StrContext::Expected(StrContextValue::Description(what)),
+    ))
+}
+
+/// Consumes `tag` if the input starts with it.
+fn eat<I>(input: &mut I, tag: &str) -> bool
+where
+    I: Stream + for<'a> Compare<&'a str>,
+{
+    match input.compare(tag) {
+        CompareResult::Ok(len) => {
+            input.next_slice(len);
+            true
+        }
+        _ => false,
+    }
+}
@@ -7,6 +7,17 @@ pub use winnow_grammar_macro::grammar;
 // Re-export winnow so generated code has access to it
 pub use winnow;
 
+// Runtime support used by generated parsers
+pub mod binary;
+pub mod diagnostic;
+pub mod error;
+pub mod float;
+pub mod integer;
+pub mod precedence;
+pub mod recover;
+pub mod report;
+pub mod state;
+
 // Re-export testing utilities from syn-grammar (grammar-kit)
 // Note: You might need to implement Testable for winnow::PResult later
 pub use syn_grammar::testing;
@@ -0,0 +1,36 @@
+//! Runtime support for `precedence` rules.
+//!
+//! A `precedence` rule compiles to a single Pratt step: it parses a prefix
+//! operator or an atom, then folds postfix, infix and ternary operators into
+//! the left operand for as long as they bind at least as tightly as the
+//! current minimum. Operands on the right are parsed by re-entering the step
+//! with a higher minimum, which is what [`climb`] provides.
+//!
+//! Each declared level `n` maps to the binding powers below. Comparing the
+//! left power against the minimum decides whether an operator is folded at
+//! this depth or left to an enclosing one.
+//!
+//! | Operator    | Left power | Right power |
+//! |-------------|------------|-------------|
+//! | `prefix n`  |            | `2n + 1`    |
+//! | `postfix n` | `2n`       |             |
+//! | `left n`    | `2n`       | `2n + 1`    |
+//! | `right n`   | `2n + 1`   | `2n`        |
+//! | `nonassoc n`| `2n`       | `2n + 1`    |
+//! | `ternary n` | `2n + 1`   | `2n`        |
+
+use winnow::error::ModalResult;
+
+/// Parses an operand, stopping before operators that bind less tightly
+/// than the given minimum.
+pub type Operand<'a, I, O, E> = &'a dyn Fn(&mut I, u32) -> ModalResult<O, E>;
+
+/// One Pratt step: an operand and the operators folded into it, given the
+/// minimum binding power and a parser for nested operands.
+pub type Step<'a, I, O, E> = &'a dyn Fn(&mut I, u32, Operand<'_, I, O, E>) -> ModalResult<O, E>;
+
+/// Runs `step` with a minimum binding power of `min`, passing it a parser
+/// that re-enters `step` for nested operands.
+pub fn climb<I, O, E>(input: &mut I, min: u32, step: Step<'_, I, O, E>) -> ModalResult<O, E> {
+    step(input, min, &|input, min| climb(input, min, step))
+}
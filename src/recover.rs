@@ -0,0 +1,425 @@
+//! Error recovery for generated parsers.
+//!
+//! `recover(rule, sync)` discards the error of a failed `rule` and yields
+//! `None`. `try_recover(rule, sync)` keeps it: a failure yields
+//! `Err(Recovered)` with the error and the input that was skipped to reach
+//! `sync`, so one parse can report every syntax error in the input. For a
+//! rule with `#[on_error(...)]`, `recover_node(rule, sync)` yields the rule's
+//! error value built by [`error_node`] instead.
+//!
+//! When `sync` is a literal or a choice of literals, the skipped input is
+//! scanned for them directly instead of trying `sync` at every token.
+//!
+//! With [`Recovery::Balanced`] (`recovery: balanced;` in the grammar), `sync`
+//! is not looked for inside `()`, `[]`, `{}` or string literals, and an
+//! unmatched closing delimiter ends the skipped input, so recovery inside a
+//! delimited pattern stops at its closer. `recover(rule)`, written without a
+//! sync token, always recovers this way and only stops at such a closer.
+//!
+//! Grammars declaring `repair: true;` also [`repair`] literals after a cut:
+//! a missing literal is assumed to be present, or an unexpected token in
+//! front of it is skipped. Each repair is logged in the [`Repairs`] kept in
+//! the parser state, up to its budget.
+
+use std::ops::Range;
+
+use winnow::error::{ContextError, ErrMode, ModalResult, ParserError};
+use winnow::stream::{AsBStr, FindSlice, Location, Stream};
+use winnow::Parser;
+
+use crate::diagnostic::{position, Diagnostic, Fix, Range as LineRange, Severity};
+use crate::error::line_col;
+use crate::report::Report;
+use crate::state::StateStream;
+
+/// A failure that was recovered from by skipping input up to a sync token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recovered<E = ContextError> {
+    /// The error of the failed rule.
+    pub error: E,
+    /// The byte offset at which the rule failed.
+    pub offset: usize,
+    /// The byte range of the skipped input, without surrounding whitespace.
+    pub span: Range<usize>,
+    /// The skipped input, without surrounding whitespace.
+    pub skipped: String,
+}
+
+impl Recovered<ContextError> {
+    /// Builds a report for the error, with `source` being the parsed text.
+    pub fn report<'a>(&self, source: &'a str) -> Report<'a> {
+        Report::from_context(&self.error, self.offset, source)
+    }
+}
+
+/// How recovery skips input on its way to the sync token.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Recovery {
+    /// Stops at the first match of the sync token.
+    #[default]
+    Flat,
+    /// Skips balanced delimiters and string literals, and stops before an
+    /// unmatched closing delimiter.
+    Balanced,
+}
+
+/// Runs `body`, returning `None` if it fails and the input can be skipped
+/// up to `sync`. See [`try_recover`].
+pub fn recover<I, O, S, E>(
+    input: &mut I,
+    body: impl Parser<I, O, E>,
+    sync: impl Parser<I, S, E>,
+    literals: &[&str],
+    recovery: Recovery,
+) -> ModalResult<Option<O>, E>
+where
+    I: Stream + Location,
+    I::Slice: AsBStr,
+    E: ParserError<I>,
+{
+    try_recover(input, body, sync, literals, recovery).map(Result::ok)
+}
+
+/// Runs `body`. If it fails, skips input until `sync` matches, consumes the
+/// sync token and returns the failure as a [`Recovered`]. When no sync token
+/// follows, the original error is returned. Cut errors are not recovered.
+///
+/// If `sync` can only start with one of `literals`, the input is scanned for
+/// them and `sync` is only tried where one occurs. With no `literals`, `sync`
+/// is tried at every token.
+///
+/// With [`Recovery::Balanced`], skipping also ends before an unmatched closing
+/// delimiter, which is left in the input. The skipped input must then contain
+/// more than whitespace.
+pub fn try_recover<I, O, S, E>(
+    input: &mut I,
+    mut body: impl Parser<I, O, E>,
+    mut sync: impl Parser<I, S, E>,
+    literals: &[&str],
+    recovery: Recovery,
+) -> ModalResult<Result<O, Recovered<E>>, E>
+where
+    I: Stream + Location,
+    I::Slice: AsBStr,
+    E: ParserError<I>,
+{
+    let start = input.checkpoint();
+    let error = match body.parse_next(input) {
+        Ok(output) => return Ok(Ok(output)),
+        Err(ErrMode::Backtrack(error)) => error,
+        Err(error) => return Err(error),
+    };
+    let offset = input.location();
+    let failed = input.checkpoint();
+
+    input.reset(&start);
+    let skip_start = input.location();
+    let Some(stop) = skip_to(input, &mut sync, literals, recovery) else {
+        input.reset(&failed);
+        return Err(ErrMode::Backtrack(error));
+    };
+    let len = input.offset_from(&start);
+    input.reset(&start);
+    let skipped = input.next_slice(len);
+
+    // Whitespace before the sync token is part of the skipped input but not
+    // of what was wrong.
+    let bytes = skipped.as_bstr();
+    let leading = bytes.iter().take_while(|b| b.is_ascii_whitespace()).count();
+    let trailing = bytes[leading..]
+        .iter()
+        .rev()
+        .take_while(|b| b.is_ascii_whitespace())
+        .count();
+    let trimmed = &bytes[leading..bytes.len() - trailing];
+    match stop {
+        Stop::Sync(synced) => input.reset(&synced),
+        // Stopping at a closer consumes nothing of its own, so skipping
+        // nothing would not make progress.
+        Stop::Closer if trimmed.is_empty() => {
+            input.reset(&failed);
+            return Err(ErrMode::Backtrack(error));
+        }
+        Stop::Closer => {}
+    }
+    Ok(Err(Recovered {
+        error,
+        offset,
+        span: skip_start + leading..skip_start + leading + trimmed.len(),
+        skipped: String::from_utf8_lossy(trimmed).into_owned(),
+    }))
+}
+
+/// The output of a [`try_recover`], with a recovered failure replaced by the
+/// value `build` makes from the span and text of the skipped input. Used by
+/// `recover_node` for rules with `#[on_error(...)]`.
+pub fn error_node<O, E>(
+    result: Result<O, Recovered<E>>,
+    build: impl FnOnce(Range<usize>, &str) -> O,
+) -> O {
+    result.unwrap_or_else(|recovered| build(recovered.span, &recovered.skipped))
+}
+
+/// Where skipping ended.
+enum Stop<C> {
+    /// Before a match of the sync token; the checkpoint is after it.
+    Sync(C),
+    /// Before an unmatched closing delimiter.
+    Closer,
+}
+
+/// Advances `input` to the next position where `sync` matches, or with
+/// [`Recovery::Balanced`] to an unmatched closing delimiter. Returns `None`
+/// if neither occurs.
+fn skip_to<I, S, E>(
+    input: &mut I,
+    sync: &mut impl Parser<I, S, E>,
+    literals: &[&str],
+    recovery: Recovery,
+) -> Option<Stop<I::Checkpoint>>
+where
+    I: Stream,
+    I::Slice: AsBStr,
+    E: ParserError<I>,
+{
+    let start = input.checkpoint();
+    let rest = input.finish();
+    input.reset(&start);
+    let rest = rest.as_bstr();
+
+    let mut nesting: Vec<u8> = Vec::new();
+    let mut in_string = false;
+    loop {
+        if recovery == Recovery::Flat && !literals.is_empty() {
+            let at = input.offset_from(&start);
+            let found = find_literal(&rest[at..], literals)?;
+            input.next_slice(found);
+        }
+        let at = input.offset_from(&start);
+        let candidate = literals.is_empty()
+            || literals
+                .iter()
+                .any(|l| rest[at..].starts_with(l.as_bytes()));
+        if candidate && nesting.is_empty() && !in_string {
+            let before = input.checkpoint();
+            if sync.parse_next(input).is_ok() {
+                let synced = input.checkpoint();
+                input.reset(&before);
+                return Some(Stop::Sync(synced));
+            }
+            input.reset(&before);
+        }
+        if recovery == Recovery::Balanced {
+            match rest.get(at).copied() {
+                Some(b'"') => in_string = !in_string,
+                Some(b'\\') if in_string => {
+                    input.next_token()?;
+                }
+                Some(b'(') if !in_string => nesting.push(b')'),
+                Some(b'[') if !in_string => nesting.push(b']'),
+                Some(b'{') if !in_string => nesting.push(b'}'),
+                Some(close @ (b')' | b']' | b'}')) if !in_string => {
+                    if nesting.last() == Some(&close) {
+                        nesting.pop();
+                    } else if nesting.is_empty() {
+                        return Some(Stop::Closer);
+                    }
+                }
+                _ => {}
+            }
+        }
+        input.next_token()?;
+    }
+}
+
+/// The offset of the first occurrence of any of `literals` in `rest`.
+fn find_literal(rest: &[u8], literals: &[&str]) -> Option<usize> {
+    if let [literal] = literals {
+        return rest.find_slice(literal.as_bytes()).map(|found| found.start);
+    }
+    // Look for a possible first byte, then compare the literals there. A
+    // literal never starts with a UTF-8 continuation byte, so every match is
+    // at a character boundary.
+    let mut from = 0;
+    while let Some(at) = rest[from..]
+        .iter()
+        .position(|b| literals.iter().any(|l| l.as_bytes().first() == Some(b)))
+    {
+        let at = from + at;
+        if literals
+            .iter()
+            .any(|l| rest[at..].starts_with(l.as_bytes()))
+        {
+            return Some(at);
+        }
+        from = at + 1;
+    }
+    None
+}
+
+/// A change to the input that let parsing continue past a syntax error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Repair {
+    /// The missing `literal` was assumed at byte `at`.
+    Inserted { literal: &'static str, at: usize },
+    /// The unexpected `text` at `span` was skipped.
+    Deleted { text: String, span: Range<usize> },
+}
+
+impl Repair {
+    /// Describes the error that was repaired, such as "missing `;`".
+    pub fn message(&self) -> String {
+        match self {
+            Repair::Inserted { literal, .. } => format!("missing `{}`", literal),
+            Repair::Deleted { text, .. } => format!("unexpected `{}`", text),
+        }
+    }
+
+    /// The byte range the repair applies to; empty for an insertion.
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            Repair::Inserted { at, .. } => *at..*at,
+            Repair::Deleted { span, .. } => span.clone(),
+        }
+    }
+
+    /// A diagnostic for the repaired error, with the repair as its fix.
+    pub fn diagnostic(&self, source: &str) -> Diagnostic {
+        let span = self.span();
+        let range = LineRange {
+            start: position(source, span.start),
+            end: position(source, span.end),
+        };
+        let (title, expected, new_text) = match self {
+            Repair::Inserted { literal, .. } => (
+                format!("insert `{}`", literal),
+                vec![format!("`{}`", literal)],
+                literal.to_string(),
+            ),
+            Repair::Deleted { text, .. } => {
+                (format!("remove `{}`", text), Vec::new(), String::new())
+            }
+        };
+        Diagnostic {
+            range,
+            severity: Severity::Error,
+            source: "winnow-grammar".to_string(),
+            message: self.message(),
+            file: None,
+            span: span.clone(),
+            line_col: line_col(source, span.start),
+            expected,
+            rules: Vec::new(),
+            fixes: vec![Fix {
+                title,
+                range,
+                new_text,
+            }],
+        }
+    }
+}
+
+/// The repairs made during a parse, and how many more are allowed.
+///
+/// Grammars with `repair: true;` need a `state` type implementing
+/// `AsMut<Repairs>`; `Repairs` itself can be used as the state.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Repairs {
+    budget: usize,
+    repairs: Vec<Repair>,
+}
+
+impl Repairs {
+    /// Allows at most `budget` repairs; after that, errors fail the parse.
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            repairs: Vec::new(),
+        }
+    }
+
+    /// The repairs made so far, in input order.
+    pub fn repairs(&self) -> &[Repair] {
+        &self.repairs
+    }
+
+    /// How many more repairs are allowed.
+    pub fn remaining(&self) -> usize {
+        self.budget - self.repairs.len()
+    }
+}
+
+impl AsMut<Repairs> for Repairs {
+    fn as_mut(&mut self) -> &mut Repairs {
+        self
+    }
+}
+
+/// Matches `literal` with `parser`. If it is not there and the repair budget
+/// allows, either the one unexpected token before it is skipped or, if the
+/// literal does not follow that token either, the literal is assumed to be
+/// present. The repair is logged and parsing continues.
+pub fn repair<I, O, E>(
+    input: &mut I,
+    literal: &'static str,
+    mut parser: impl Parser<I, O, E>,
+) -> ModalResult<(), E>
+where
+    I: Stream + Location + StateStream,
+    I::State: AsMut<Repairs>,
+    I::Slice: AsBStr,
+    E: ParserError<I>,
+{
+    let start = input.checkpoint();
+    let error = match parser.parse_next(input) {
+        Ok(_) => return Ok(()),
+        Err(ErrMode::Backtrack(error)) => error,
+        Err(error) => return Err(error),
+    };
+    if input.state_mut().as_mut().remaining() == 0 {
+        return Err(ErrMode::Cut(error));
+    }
+    input.reset(&start);
+    let at = input.location();
+
+    // Skip whitespace, then one word or one other token.
+    let rest = input.finish();
+    input.reset(&start);
+    let rest = rest.as_bstr();
+    let blank = rest.iter().take_while(|b| b.is_ascii_whitespace()).count();
+    let word = rest[blank..]
+        .iter()
+        .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_')
+        .count();
+    input.next_slice(blank);
+    let token_start = input.checkpoint();
+    let skipped = if word > 0 {
+        Some(input.next_slice(word))
+    } else {
+        input.next_token().map(|_| {
+            let len = input.offset_from(&token_start);
+            input.reset(&token_start);
+            input.next_slice(len)
+        })
+    };
+    if let Some(skipped) = skipped {
+        let span = at + blank..input.location();
+        if parser.parse_next(input).is_ok() {
+            let text = String::from_utf8_lossy(skipped.as_bstr()).into_owned();
+            log(input, Repair::Deleted { text, span });
+            return Ok(());
+        }
+    }
+
+    input.reset(&start);
+    log(input, Repair::Inserted { literal, at });
+    Ok(())
+}
+
+fn log<I>(input: &mut I, repair: Repair)
+where
+    I: StateStream,
+    I::State: AsMut<Repairs>,
+{
+    input.state_mut().as_mut().repairs.push(repair);
+}
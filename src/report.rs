@@ -0,0 +1,267 @@
+//! Rustc-style rendering of parse errors.
+//!
+//! A [`Report`] combines a failed parse with its source text:
+//!
+//! ```text
+//! error: expected one of `+`, `-`, found `*`
+//!  --> calc.txt:1:3
+//!   |
+//! 1 | 1 * 2
+//!   |   ^ expected one of `+`, `-`
+//!   |
+//!   = note: while parsing `expr` > `binop`
+//! ```
+//!
+//! [`Report::render`] produces plain text (also used by `Display`) and
+//! [`Report::render_ansi`] the same layout with terminal colors.
+
+use std::fmt;
+
+use winnow::error::{ContextError, ParseError, StrContext, StrContextValue};
+
+use crate::error::{describe, expected, line_col, suggestion};
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// A parse error located in its source text.
+#[derive(Debug, Clone)]
+pub struct Report<'a> {
+    file: Option<&'a str>,
+    source: &'a str,
+    offset: usize,
+    expected: Vec<String>,
+    label: String,
+    rules: Vec<String>,
+    literals: Vec<String>,
+    suggestion: Option<String>,
+}
+
+impl<'a> Report<'a> {
+    /// Builds a report for `error`, raised while parsing `source`.
+    pub fn new<I>(error: &ParseError<I, ContextError>, source: &'a str) -> Self {
+        Self::from_context(error.inner(), error.offset(), source)
+    }
+
+    /// Builds a report for a bare error positioned at byte `offset` of `source`.
+    pub fn from_context(error: &ContextError, offset: usize, source: &'a str) -> Self {
+        // Rule labels are added as the error unwinds, innermost first.
+        let mut rules: Vec<String> = error
+            .context()
+            .filter_map(|c| match c {
+                StrContext::Label(name) => Some(name.to_string()),
+                _ => None,
+            })
+            .collect();
+        rules.reverse();
+        let literals = error
+            .context()
+            .filter_map(|c| match c {
+                StrContext::Expected(StrContextValue::StringLiteral(s)) => Some(s.to_string()),
+                StrContext::Expected(StrContextValue::CharLiteral(c)) => Some(c.to_string()),
+                _ => None,
+            })
+            .collect();
+
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        Self {
+            file: None,
+            source,
+            offset,
+            expected: expected(error),
+            label: describe(error),
+            rules,
+            literals,
+            suggestion: suggestion(error),
+        }
+    }
+
+    /// Sets the file name shown after `-->`.
+    pub fn with_file(mut self, file: &'a str) -> Self {
+        self.file = Some(file);
+        self
+    }
+
+    /// The byte offset of the error in the source.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The 1-based line and column of the error.
+    pub fn line_col(&self) -> (usize, usize) {
+        line_col(self.source, self.offset)
+    }
+
+    /// The expected items, in the order they were tried.
+    pub fn expected(&self) -> &[String] {
+        &self.expected
+    }
+
+    /// The rules being parsed when the error occurred, outermost first.
+    pub fn rules(&self) -> &[String] {
+        &self.rules
+    }
+
+    /// The keyword the unexpected word is probably a misspelling of.
+    pub fn suggestion(&self) -> Option<&str> {
+        self.suggestion.as_deref()
+    }
+
+    /// The source text the report points into.
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// The file name set with [`Report::with_file`].
+    pub fn file(&self) -> Option<&'a str> {
+        self.file
+    }
+
+    /// The literal tokens among the expected items, without quotes.
+    pub fn literals(&self) -> &[String] {
+        &self.literals
+    }
+
+    /// The text at the error position: an identifier or number, or a single
+    /// character. Empty at the end of the input.
+    pub fn found(&self) -> &'a str {
+        let rest = &self.source[self.offset..];
+        let word = |c: char| c.is_alphanumeric() || c == '_';
+        let len = match rest.chars().next() {
+            None => 0,
+            Some(c) if word(c) => rest.find(|c| !word(c)).unwrap_or(rest.len()),
+            Some(c) if c == '\n' || c == '\r' => 0,
+            Some(c) => c.len_utf8(),
+        };
+        &rest[..len]
+    }
+
+    /// The headline, such as "expected `;`, found `}`".
+    pub fn message(&self) -> String {
+        let found = match self.found() {
+            "" if self.offset == self.source.len() => "end of input".to_string(),
+            "" => "end of line".to_string(),
+            found => format!("`{}`", found),
+        };
+        if self.expected.is_empty() {
+            format!("unexpected {}", found)
+        } else {
+            format!("{}, found {}", self.label, found)
+        }
+    }
+
+    /// Renders the report as plain text.
+    pub fn render(&self) -> String {
+        self.render_with(&Style::PLAIN)
+    }
+
+    /// Renders the report with ANSI colors for terminals.
+    pub fn render_ansi(&self) -> String {
+        self.render_with(&Style::ANSI)
+    }
+
+    fn render_with(&self, style: &Style) -> String {
+        let (line, col) = self.line_col();
+        let line_start = self.source[..self.offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = self.source[self.offset..]
+            .find('\n')
+            .map_or(self.source.len(), |i| self.offset + i);
+        let text = self.source[line_start..line_end].trim_end_matches('\r');
+
+        let number = line.to_string();
+        let pad = " ".repeat(number.len());
+        // Keep tabs so the caret lines up with the source line.
+        let indent: String = self.source[line_start..self.offset]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let carets = "^".repeat(self.found().chars().count().max(1));
+        let Style {
+            error,
+            gutter,
+            bold,
+            reset,
+        } = style;
+
+        let mut out = String::new();
+        out += &format!("{error}error{reset}{bold}: {}{reset}\n", self.message());
+        out += &format!(
+            "{pad}{gutter}-->{reset} {}:{}:{}\n",
+            self.file.unwrap_or("<input>"),
+            line,
+            col
+        );
+        out += &format!("{pad} {gutter}|{reset}\n");
+        out += &format!("{gutter}{number} |{reset} {text}\n");
+        out += &format!(
+            "{pad} {gutter}|{reset} {indent}{error}{carets} {}{reset}\n",
+            self.label
+        );
+        if !self.rules.is_empty() {
+            // A rule that called itself is shown once, with its depth.
+            let mut stack: Vec<(&str, usize)> = Vec::new();
+            for rule in &self.rules {
+                match stack.last_mut() {
+                    Some((last, count)) if last == rule => *count += 1,
+                    _ => stack.push((rule, 1)),
+                }
+            }
+            let stack: Vec<String> = stack
+                .into_iter()
+                .map(|(rule, count)| match count {
+                    1 => format!("`{}`", rule),
+                    _ => format!("`{}` (x{})", rule, count),
+                })
+                .collect();
+            out += &format!("{pad} {gutter}|{reset}\n");
+            out += &format!(
+                "{pad} {gutter}={reset} {bold}note{reset}: while parsing {}\n",
+                stack.join(" > ")
+            );
+        }
+        if let Some(keyword) = &self.suggestion {
+            if self.rules.is_empty() {
+                out += &format!("{pad} {gutter}|{reset}\n");
+            }
+            out += &format!(
+                "{pad} {gutter}={reset} {bold}help{reset}: did you mean `{}`?\n",
+                keyword
+            );
+        }
+        out
+    }
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render())
+    }
+}
+
+struct Style {
+    error: &'static str,
+    gutter: &'static str,
+    bold: &'static str,
+    reset: &'static str,
+}
+
+impl Style {
+    const PLAIN: Style = Style {
+        error: "",
+        gutter: "",
+        bold: "",
+        reset: "",
+    };
+    const ANSI: Style = Style {
+        error: RED,
+        gutter: BLUE,
+        bold: BOLD,
+        reset: RESET,
+    };
+}
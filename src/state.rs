@@ -0,0 +1,51 @@
+//! Mutable user state threaded through generated parsers.
+//!
+//! A grammar that declares `state: MyState;` parses any input implementing
+//! [`StateStream`], usually [`winnow::stream::Stateful`]. Actions and
+//! predicates reach the state through the reserved `state` binding.
+//!
+//! Wherever a generated parser backtracks, the state is restored together
+//! with the input: alternatives, options, repetitions and the body of a
+//! `recover`, `try_recover` or `recover_node` are each wrapped in
+//! [`rollback`].
+
+use winnow::error::ErrMode;
+use winnow::stream::{Stateful, Stream};
+use winnow::Parser;
+
+/// An input stream that carries user state alongside the parsed text.
+pub trait StateStream {
+    type State;
+
+    fn state(&self) -> &Self::State;
+
+    fn state_mut(&mut self) -> &mut Self::State;
+}
+
+impl<I, S> StateStream for Stateful<I, S> {
+    type State = S;
+
+    fn state(&self) -> &S {
+        &self.state
+    }
+
+    fn state_mut(&mut self) -> &mut S {
+        &mut self.state
+    }
+}
+
+/// Runs `parser`, restoring the state it started with if it backtracks.
+pub fn rollback<I, O, E>(mut parser: impl Parser<I, O, E>) -> impl Parser<I, O, E>
+where
+    I: Stream + StateStream,
+    I::State: Clone,
+{
+    move |input: &mut I| {
+        let checkpoint = input.state().clone();
+        let result = parser.parse_next(input);
+        if let Err(ErrMode::Backtrack(_)) = &result {
+            *input.state_mut() = checkpoint;
+        }
+        result
+    }
+}
@@ -0,0 +1,49 @@
+use winnow::prelude::*;
+use winnow::stream::LocatingSlice;
+use winnow_grammar::grammar;
+
+grammar! {
+    grammar BackRef {
+        // XML-like element whose closing tag must repeat the opening one.
+        pub rule element -> (String, String) =
+            "<" open:ident ">" body:ident "</" =open ">" -> { (open, body) }
+
+        // Rust-style raw string: r#"..."# with any number of `#`.
+        pub rule raw_string -> usize =
+            "r" hashes:hash_run "\"" (not(("\"" =hashes)) any)* "\"" =hashes -> { hashes.len() }
+
+        rule hash_run -> String =
+            h:"#"* -> { "#".repeat(h.len()) }
+
+        pub rule closing -> String =
+            open:ident "/" close:=open -> { close }
+    }
+}
+
+#[test]
+fn test_matching_tags() {
+    let result = BackRef::parse_element.parse(LocatingSlice::new("<item> x </item>"));
+    assert_eq!(result.unwrap(), ("item".to_string(), "x".to_string()));
+}
+
+#[test]
+fn test_mismatched_tags() {
+    let result = BackRef::parse_element.parse(LocatingSlice::new("<item> x </other>"));
+    let err = result.unwrap_err();
+    assert!(err.to_string().contains("same text as `open`"), "{}", err);
+}
+
+#[test]
+fn test_raw_string() {
+    let result = BackRef::parse_raw_string.parse(LocatingSlice::new(r###"r##"a "# b"##"###));
+    assert_eq!(result.unwrap(), 2);
+
+    let result = BackRef::parse_raw_string.parse(LocatingSlice::new(r###"r##"a"#"###));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_bound_back_reference() {
+    let result = BackRef::parse_closing.parse(LocatingSlice::new("abc / abc"));
+    assert_eq!(result.unwrap(), "abc");
+}
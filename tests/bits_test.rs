@@ -0,0 +1,71 @@
+use winnow::prelude::*;
+use winnow::stream::LocatingSlice;
+use winnow_grammar::grammar;
+
+#[derive(Debug, PartialEq)]
+pub struct Ipv4Header {
+    pub version: u8,
+    pub ihl: u8,
+    pub flags: u8,
+    pub offset: u16,
+    pub ttl: u8,
+}
+
+grammar! {
+    grammar Packed {
+        input: bytes;
+
+        pub rule ipv4 -> Ipv4Header =
+            bits { version:u4 ihl:u4 } _tos:be_u8 _len:be_u16 _id:be_u16
+            bits { flags:u3 offset:u13 } ttl:be_u8
+            -> { Ipv4Header { version, ihl, flags, offset, ttl } }
+
+        // Unnamed fields are padding.
+        pub rule padded -> (u8, u32) =
+            bits { flag:u1 _:u3 u4 value:u24 } -> { (flag, value) }
+
+        pub rule grouped -> (u8, u8) =
+            fields:bits { hi:u4 lo:u4 } -> { fields }
+
+        // Fields can feed later counts.
+        pub rule chunk -> Vec<u8> =
+            bits { kind:u2 len:u6 } data:take(len) -> { let _ = kind; data.as_ref().to_vec() }
+    }
+}
+
+#[test]
+fn test_ipv4_header() {
+    let input = b"\x45\x00\x00\x54\x1c\x46\x40\x00\x40";
+    let result = Packed::parse_ipv4.parse(LocatingSlice::new(&input[..]));
+    assert_eq!(
+        result.unwrap(),
+        Ipv4Header {
+            version: 4,
+            ihl: 5,
+            flags: 0b010,
+            offset: 0,
+            ttl: 64,
+        }
+    );
+}
+
+#[test]
+fn test_padding_and_wide_fields() {
+    let result = Packed::parse_padded.parse(LocatingSlice::new(&b"\x8f\x12\x34\x56"[..]));
+    assert_eq!(result.unwrap(), (1, 0x12_3456));
+
+    let result = Packed::parse_padded.parse(LocatingSlice::new(&b"\x8f\x12"[..]));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_bound_block() {
+    let result = Packed::parse_grouped.parse(LocatingSlice::new(&b"\xa5"[..]));
+    assert_eq!(result.unwrap(), (0xa, 0x5));
+}
+
+#[test]
+fn test_field_as_count() {
+    let result = Packed::parse_chunk.parse(LocatingSlice::new(&b"\x43abc"[..]));
+    assert_eq!(result.unwrap(), b"abc".to_vec());
+}
@@ -0,0 +1,85 @@
+use winnow::prelude::*;
+use winnow::stream::LocatingSlice;
+use winnow_grammar::grammar;
+
+grammar! {
+    grammar Counted {
+        // Netstring: "<len>:<data>,"
+        pub rule netstring -> String =
+            n:usize ":" data:take(n) "," -> { data.to_string() }
+
+        // Pascal-style list: a count followed by that many items.
+        pub rule list -> Vec<u32> =
+            n:u32 items:u32{n} -> { items }
+
+        pub rule pair -> Vec<String> =
+            p:ident{2} -> { p }
+
+        pub rule tlv -> (u8, String) =
+            tag:u8 len:u8 ":" value:take(len - 1) "." -> { (tag, value.to_string()) }
+    }
+}
+
+grammar! {
+    grammar Shadowed {
+        pub rule twice -> String =
+            "twice" s:take(2) -> { s }
+
+        // A rule of the grammar takes the place of the built-in `take(n)`.
+        rule take(n: usize) -> String =
+            s:ident -> { s.repeat(n) }
+    }
+}
+
+#[test]
+fn test_netstring() {
+    let result = Counted::parse_netstring.parse(LocatingSlice::new("5:hello,"));
+    assert_eq!(result.unwrap(), "hello");
+
+    // Data is taken verbatim, including spaces.
+    let result = Counted::parse_netstring.parse(LocatingSlice::new("3: a ,"));
+    assert_eq!(result.unwrap(), " a ");
+
+    let result = Counted::parse_netstring.parse(LocatingSlice::new("6:hello,"));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_netstring_counts_bytes() {
+    let result = Counted::parse_netstring.parse(LocatingSlice::new("6:héllo,"));
+    assert_eq!(result.unwrap(), "héllo");
+
+    // Two bytes end inside `é`.
+    let result = Counted::parse_netstring.parse(LocatingSlice::new("2:hé,"));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_user_rule_named_take() {
+    let result = Shadowed::parse_twice.parse(LocatingSlice::new("twice ab"));
+    assert_eq!(result.unwrap(), "abab");
+}
+
+#[test]
+fn test_counted_repetition() {
+    let result = Counted::parse_list.parse(LocatingSlice::new("3 10 20 30"));
+    assert_eq!(result.unwrap(), vec![10, 20, 30]);
+
+    let result = Counted::parse_list.parse(LocatingSlice::new("3 10 20"));
+    assert!(result.is_err());
+
+    let result = Counted::parse_list.parse(LocatingSlice::new("0"));
+    assert_eq!(result.unwrap(), Vec::<u32>::new());
+}
+
+#[test]
+fn test_literal_count() {
+    let result = Counted::parse_pair.parse(LocatingSlice::new("a b"));
+    assert_eq!(result.unwrap(), vec!["a".to_string(), "b".to_string()]);
+}
+
+#[test]
+fn test_count_expression() {
+    let result = Counted::parse_tlv.parse(LocatingSlice::new("7 4:abc."));
+    assert_eq!(result.unwrap(), (7, "abc".to_string()));
+}
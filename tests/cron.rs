@@ -24,7 +24,7 @@ pub enum Field {
 grammar! {
     grammar Cron {
         pub rule schedule -> Schedule =
-            sec:field min:field hour:field dom:field mon:field dow:field -> {
+            sec:field(59) min:field(59) hour:field(23) dom:field(31) mon:field(12) dow:field(7) -> {
                 Schedule {
                     second: sec,
                     minute: min,
@@ -35,33 +35,53 @@ grammar! {
                 }
             }
 
-        rule field -> Field =
-            l:list -> { if l.len() == 1 { l.into_iter().next().unwrap() } else { Field::List(l) } }
+        rule field(max: u32) -> Field =
+            l:list(max) -> { if l.len() == 1 { l.into_iter().next().unwrap() } else { Field::List(l) } }
 
-        rule list -> Vec<Field> =
-            base:base_field "," rest:list -> { let mut rest = rest; rest.insert(0, base); rest }
-          | base:base_field -> { vec![base] }
+        rule list(max: u32) -> Vec<Field> =
+            base:base_field(max) "," rest:list(max) -> { let mut rest = rest; rest.insert(0, base); rest }
+          | base:base_field(max) -> { vec![base] }
 
-        rule base_field -> Field =
-            f:range_or_val s:step? -> {
+        #[expect("a cron field")]
+        rule base_field(max: u32) -> Field =
+            f:range_or_val(max) s:step(max)? -> {
                 match s {
                     Some(step) => Field::Step(Box::new(f), step),
                     None => f,
                 }
             }
-          | "*" s:step? -> {
+          | "*" s:step(max)? -> {
                 match s {
                     Some(step) => Field::Step(Box::new(Field::Any), step),
                     None => Field::Any,
                 }
             }
 
-        rule range_or_val -> Field =
-            a:u32 "-" b:u32 -> { Field::Range(a, b) }
-          | v:u32 -> { Field::Value(v) }
+        rule range_or_val(max: u32) -> Field =
+            a:value(max) "-" b:value(max) -> { Field::Range(a, b) }
+          | v:value(max) -> { Field::Value(v) }
 
-        rule step -> u32 =
-            "/" n:u32 -> { n }
+        rule step(max: u32) -> u32 =
+            "/" n:value(max) -> { n }
+
+        // Each field is told apart by its largest value.
+        rule value(max: u32) -> u32 =
+            &{ max == 59 } v:minute -> { v }
+          | &{ max == 23 } v:hour -> { v }
+          | &{ max == 31 } v:day_of_month -> { v }
+          | &{ max == 12 } v:month -> { v }
+          | &{ max == 7 } v:day_of_week -> { v }
+
+        rule minute -> u32 = v:u32 in 0..=59 -> { v }
+
+        pub rule hour -> u32 = v:u32 in 0..=23 -> { v }
+
+        rule day_of_month -> u32 = v:u32 in 1..=31 -> { v }
+
+        rule month -> u32 = v:u32 in 1..=12 -> { v }
+
+        // Sunday is both 0 and 7.
+        rule day_of_week -> u32 = v:u32 in 0..=7 -> { v }
     }
 }
 
@@ -113,3 +133,43 @@ fn test_messy_whitespace() {
     assert_eq!(result.second, Field::Value(0));
     assert_eq!(result.minute, Field::Value(30));
 }
+
+#[test]
+fn test_out_of_range_value() {
+    // Each field has its own range.
+    for source in [
+        "60 30 9 * * 1-5",
+        "0 60 9 * * 1-5",
+        "0 30 24 * * 1-5",
+        "0 30 9 0 * 1-5",
+        "0 30 9 32 * 1-5",
+        "0 30 9 * 13 1-5",
+        "0 30 9 * * 1-8",
+    ] {
+        let result = Cron::parse_schedule.parse(LocatingSlice::new(source));
+        assert!(result.is_err(), "{source}");
+    }
+
+    let result = Cron::parse_schedule.parse(LocatingSlice::new("59 59 23 31 12 7"));
+    assert!(result.is_ok());
+
+    let err = Cron::parse_hour
+        .parse(LocatingSlice::new(" 24"))
+        .unwrap_err();
+    assert_eq!(err.offset(), 1);
+    assert!(err.inner().to_string().contains("expected value in 0..=23"));
+}
+
+#[test]
+fn test_field_description() {
+    let source = "0 30 9 * x 1-5";
+    let err = Cron::parse_schedule
+        .parse(LocatingSlice::new(source))
+        .unwrap_err();
+    assert_eq!(err.offset(), 9);
+    assert_eq!(
+        winnow_grammar::error::describe(err.inner()),
+        "expected a cron field"
+    );
+    assert!(!err.to_string().contains("base_field"));
+}
@@ -0,0 +1,102 @@
+// `ParserError` still names the deprecated `ErrorKind` in winnow 0.6.
+#![allow(deprecated)]
+
+use winnow::error::{AddContext, ErrorKind, ParserError, StrContext};
+use winnow::prelude::*;
+use winnow::stream::{LocatingSlice, Stream};
+use winnow_grammar::grammar;
+
+/// A project-specific error collecting what was expected and where.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigError {
+    pub expected: Vec<String>,
+    pub rules: Vec<&'static str>,
+}
+
+impl<I: Stream> ParserError<I> for ConfigError {
+    fn from_error_kind(_input: &I, _kind: ErrorKind) -> Self {
+        Self::default()
+    }
+
+    fn append(self, _input: &I, _start: &I::Checkpoint, _kind: ErrorKind) -> Self {
+        self
+    }
+
+    fn or(mut self, other: Self) -> Self {
+        self.expected.extend(other.expected);
+        self
+    }
+}
+
+impl<I: Stream> AddContext<I, StrContext> for ConfigError {
+    fn add_context(mut self, _input: &I, _start: &I::Checkpoint, context: StrContext) -> Self {
+        match context {
+            StrContext::Expected(value) => self.expected.push(value.to_string()),
+            StrContext::Label(rule) => self.rules.push(rule),
+            _ => {}
+        }
+        self
+    }
+}
+
+grammar! {
+    grammar Settings {
+        error: ConfigError;
+
+        pub rule entry -> (String, i32) =
+            key:ident "=" value:i32 ";" -> { (key, value) }
+
+        pub rule switch -> bool =
+            "on" -> { true }
+          | "off" -> { false }
+
+        pub rule port -> u16 =
+            "port" => p:u16 in 1..=1024 -> { p }
+
+        pub rule entries -> Vec<Option<(String, i32)>> =
+            items:recover(entry, ";")* -> { items }
+    }
+}
+
+#[test]
+fn test_success() {
+    let result = Settings::parse_entry.parse(LocatingSlice::new("x = 1;"));
+    assert_eq!(result.unwrap(), ("x".to_string(), 1));
+}
+
+#[test]
+fn test_custom_error_is_returned() {
+    let err = Settings::parse_entry
+        .parse(LocatingSlice::new("x = y;"))
+        .unwrap_err();
+    let error: &ConfigError = err.inner();
+    assert_eq!(err.offset(), 4);
+    assert_eq!(error.expected, ["integer"]);
+    assert_eq!(error.rules, ["entry"]);
+}
+
+#[test]
+fn test_alternatives_combine_with_or() {
+    let err = Settings::parse_switch
+        .parse(LocatingSlice::new("maybe"))
+        .unwrap_err();
+    assert_eq!(err.inner().expected, ["`on`", "`off`"]);
+}
+
+#[test]
+fn test_range_and_cut() {
+    let err = Settings::parse_port
+        .parse(LocatingSlice::new("port 8080"))
+        .unwrap_err();
+    assert_eq!(err.offset(), 5);
+    assert_eq!(err.inner().expected, ["value in 1..=1024"]);
+}
+
+#[test]
+fn test_recover() {
+    let result = Settings::parse_entries.parse(LocatingSlice::new("a = 1; b = ?; c = 3;"));
+    assert_eq!(
+        result.unwrap(),
+        [Some(("a".to_string(), 1)), None, Some(("c".to_string(), 3))]
+    );
+}
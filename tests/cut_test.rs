@@ -0,0 +1,145 @@
+use winnow::error::ErrMode;
+use winnow::prelude::*;
+use winnow::stream::LocatingSlice;
+use winnow_grammar::grammar;
+
+grammar! {
+    grammar Scoped {
+        // The cut commits to the first alternative of the group only.
+        pub rule group -> i32 =
+            ("a" => "b" | "c" "c") n:i32 -> { n }
+          | "a" "x" n:i32 -> { n + 100 }
+
+        // A failure after the cut fails the optional instead of yielding `None`.
+        pub rule optional -> i32 =
+            ("+" => "+")? n:i32 -> { n }
+          | "+" "-" n:i32 -> { -n }
+
+        // A failure after the cut fails the repetition instead of ending it.
+        pub rule list -> i32 =
+            "[" (first:i32) ("," => i32)* "]" -> { first }
+          | "[" n:i32 "," ident "]" -> { n }
+
+        // A cut inside delimiters commits the rest of the alternative.
+        pub rule call -> i32 =
+            "f" paren("x" => n:i32) m:i32 -> { n + m }
+          | "f" paren("x" n:i32) "!" -> { n }
+
+        // A cut at the top of a rule reaches the caller.
+        pub rule committed -> i32 =
+            "let" => n:i32 -> { n }
+          | "let" "mut" n:i32 -> { n }
+
+        pub rule sum -> i32 =
+            l:sum op:("+" => "+" | "-" "-") r:term -> { l + r }
+          | t:term -> { t }
+
+        rule term -> i32 =
+            "(" => v:i32 ")" -> { v }
+          | v:i32 -> { v }
+
+        // The cut inside `item` is its own and reaches the caller, even
+        // from inside a repetition that scopes its own cut.
+        pub rule args -> i32 =
+            "(" (first:i32) ("," => item)* ")" -> { first }
+          | "(" n:i32 "," ")" -> { -n }
+
+        rule item -> i32 =
+            "&" => n:i32 -> { n }
+          | n:i32 -> { n }
+
+        // A failure after the cut fails the counted repetition instead of
+        // committing the rule's alternative.
+        pub rule twice -> i32 =
+            ("a" => "b"){2} -> { 2 }
+          | "a" "b" "a" "c" -> { 1 }
+    }
+}
+
+#[test]
+fn test_cut_in_group_backtracks_to_rule() {
+    let result = Scoped::parse_group.parse(LocatingSlice::new("a b 1"));
+    assert_eq!(result.unwrap(), 1);
+
+    let result = Scoped::parse_group.parse(LocatingSlice::new("a x 5"));
+    assert_eq!(result.unwrap(), 105);
+}
+
+#[test]
+fn test_cut_in_optional() {
+    let parse = |s| Scoped::parse_optional.parse(LocatingSlice::new(s));
+    assert_eq!(parse("+ + 3").unwrap(), 3);
+    assert_eq!(parse("3").unwrap(), 3);
+    assert_eq!(parse("+ - 3").unwrap(), -3);
+}
+
+#[test]
+fn test_cut_in_repetition() {
+    let parse = |s| Scoped::parse_list.parse(LocatingSlice::new(s));
+    assert_eq!(parse("[1, 2, 3]").unwrap(), 1);
+    assert_eq!(parse("[7, x]").unwrap(), 7);
+
+    let err = parse("[1, 2, ]").unwrap_err();
+    assert_eq!(err.offset(), 7);
+}
+
+#[test]
+fn test_cut_in_delimiters_commits_alternative() {
+    let parse = |s| Scoped::parse_call.parse(LocatingSlice::new(s));
+    assert_eq!(parse("f (x 1) 2").unwrap(), 3);
+
+    // Without the cut the second alternative would match.
+    let err = parse("f (x 1) !").unwrap_err();
+    assert_eq!(err.offset(), 8);
+}
+
+#[test]
+fn test_rule_level_cut_reaches_caller() {
+    let mut input = LocatingSlice::new("let mut 1");
+    let result = Scoped::parse_committed(&mut input);
+    assert!(matches!(result, Err(ErrMode::Cut(_))));
+}
+
+#[test]
+fn test_cut_in_left_recursive_loop() {
+    let parse = |s| Scoped::parse_sum.parse(LocatingSlice::new(s));
+    assert_eq!(parse("1 + + 2 - - 3").unwrap(), 6);
+
+    // The scoped cut in the operator group ends the loop with a backtrack.
+    let mut input = LocatingSlice::new("1 + - 2");
+    assert_eq!(Scoped::parse_sum(&mut input).unwrap(), 1);
+    assert_eq!(*input, " + - 2");
+
+    // A rule-level cut in an operand is still an error.
+    let mut input = LocatingSlice::new("1 - - (2");
+    assert!(matches!(
+        Scoped::parse_sum(&mut input),
+        Err(ErrMode::Cut(_))
+    ));
+}
+
+#[test]
+fn test_cut_in_called_rule_is_not_scoped() {
+    let parse = |s| Scoped::parse_args.parse(LocatingSlice::new(s));
+    assert_eq!(parse("(1, 2, & 3)").unwrap(), 1);
+
+    // The repetition's own cut after "," fails it with a backtrack.
+    assert_eq!(parse("(4, )").unwrap(), -4);
+
+    // The cut inside `item` fails the whole parse.
+    let mut input = LocatingSlice::new("(1, & x)");
+    assert!(matches!(
+        Scoped::parse_args(&mut input),
+        Err(ErrMode::Cut(_))
+    ));
+}
+
+#[test]
+fn test_cut_in_counted_repetition() {
+    let parse = |s| Scoped::parse_twice.parse(LocatingSlice::new(s));
+    assert_eq!(parse("a b a b").unwrap(), 2);
+    assert_eq!(parse("a b a c").unwrap(), 1);
+
+    let err = parse("a b a d").unwrap_err();
+    assert_eq!(err.offset(), 6);
+}
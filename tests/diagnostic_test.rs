@@ -0,0 +1,79 @@
+use winnow::prelude::*;
+use winnow::stream::LocatingSlice;
+use winnow_grammar::diagnostic::{position, Diagnostic, Position, Severity};
+use winnow_grammar::grammar;
+use winnow_grammar::report::Report;
+
+grammar! {
+    grammar Config {
+        pub rule pair -> Vec<(String, i64)> =
+            first:entry second:entry -> { vec![first, second] }
+
+        rule entry -> (String, i64) =
+            key:ident "=" value:i64 ";" -> { (key, value) }
+    }
+}
+
+#[test]
+fn test_missing_token() {
+    let source = "port = 80\nhost = 1;";
+    let err = Config::parse_pair
+        .parse(LocatingSlice::new(source))
+        .unwrap_err();
+    let diagnostic = Config::diagnostic(&err, source);
+
+    assert_eq!(diagnostic.severity, Severity::Error);
+    assert_eq!(diagnostic.source, "Config");
+    assert_eq!(diagnostic.message, "expected `;`, found `host`");
+    assert_eq!(diagnostic.span, 10..14);
+    assert_eq!(diagnostic.line_col, (2, 1));
+    assert_eq!(diagnostic.rules, ["pair", "entry"]);
+    assert_eq!(
+        diagnostic.to_json(),
+        concat!(
+            r#"{"range":{"start":{"line":1,"character":0},"end":{"line":1,"character":4}},"#,
+            r#""severity":1,"source":"Config","message":"expected `;`, found `host`","#,
+            r#""data":{"span":{"start":10,"end":14},"line":2,"column":1,"#,
+            r#""expected":["`;`"],"rules":["pair","entry"],"#,
+            r#""fixes":[{"title":"insert `;`","#,
+            r#""range":{"start":{"line":0,"character":9},"end":{"line":0,"character":9}},"#,
+            r#""newText":";"}]}}"#,
+        )
+    );
+}
+
+#[test]
+fn test_from_report() {
+    let source = "port = \"80\";";
+    let err = Config::parse_pair
+        .parse(LocatingSlice::new(source))
+        .unwrap_err();
+    let diagnostic: Diagnostic = Report::new(&err, source).with_file("a.conf").into();
+
+    assert_eq!(diagnostic.source, "winnow-grammar");
+    assert_eq!(diagnostic.file.as_deref(), Some("a.conf"));
+    assert_eq!(diagnostic.expected, ["integer"]);
+    // Only a missing literal can be inserted.
+    assert!(diagnostic.fixes.is_empty());
+    assert!(diagnostic
+        .to_json()
+        .contains(r#""message":"expected integer, found `\"`","data":{"file":"a.conf","#));
+}
+
+#[test]
+fn test_utf16_position() {
+    assert_eq!(
+        position("a😀b", 5),
+        Position {
+            line: 0,
+            character: 3
+        }
+    );
+    assert_eq!(
+        position("ä\nöx", 5),
+        Position {
+            line: 1,
+            character: 1
+        }
+    );
+}
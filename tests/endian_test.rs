@@ -0,0 +1,79 @@
+use winnow::prelude::*;
+use winnow::stream::LocatingSlice;
+use winnow_grammar::grammar;
+
+grammar! {
+    grammar Packet {
+        input: bytes;
+
+        pub rule header -> (u16, u32, i16) =
+            "PK" magic:be_u16 len:le_u32 delta:be_i16 -> { (magic, len, delta) }
+
+        pub rule color -> u32 =
+            rgb:be_u24 -> { rgb }
+
+        pub rule sample -> (f32, f64) =
+            a:le_f32 b:be_f64 -> { (a, b) }
+
+        pub rule varints -> (u64, i64) =
+            a:uleb128 b:sleb128 -> { (a, b) }
+
+        // Length-prefixed payload with a varint length
+        pub rule message -> Vec<u8> =
+            "MSG" len:uleb128 body:take(len) -> { body.as_ref().to_vec() }
+    }
+}
+
+#[test]
+fn test_fixed_width() {
+    let input = b"PK\x12\x34\x78\x56\x34\x12\xff\xfe";
+    let result = Packet::parse_header.parse(LocatingSlice::new(&input[..]));
+    assert_eq!(result.unwrap(), (0x1234, 0x1234_5678, -2));
+
+    let result = Packet::parse_header.parse(LocatingSlice::new(&b"PK\x12"[..]));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_u24() {
+    let result = Packet::parse_color.parse(LocatingSlice::new(&b"\xff\x80\x00"[..]));
+    assert_eq!(result.unwrap(), 0xff8000);
+}
+
+#[test]
+fn test_floats() {
+    let mut input = 1.5f32.to_le_bytes().to_vec();
+    input.extend_from_slice(&(-0.25f64).to_be_bytes());
+    let result = Packet::parse_sample.parse(LocatingSlice::new(&input[..]));
+    assert_eq!(result.unwrap(), (1.5, -0.25));
+}
+
+#[test]
+fn test_leb128() {
+    let result = Packet::parse_varints.parse(LocatingSlice::new(&b"\xe5\x8e\x26\xc0\xbb\x78"[..]));
+    assert_eq!(result.unwrap(), (624_485, -123_456));
+
+    let result = Packet::parse_varints.parse(LocatingSlice::new(&b"\x00\x7f"[..]));
+    assert_eq!(result.unwrap(), (0, -1));
+
+    // Eleven bytes cannot fit in a u64
+    let overflow = b"\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\x01\x00";
+    let result = Packet::parse_varints.parse(LocatingSlice::new(&overflow[..]));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_leb128_limits() {
+    let max = b"\xff\xff\xff\xff\xff\xff\xff\xff\xff\x01\x80\x80\x80\x80\x80\x80\x80\x80\x80\x7f";
+    let result = Packet::parse_varints.parse(LocatingSlice::new(&max[..]));
+    assert_eq!(result.unwrap(), (u64::MAX, i64::MIN));
+}
+
+#[test]
+fn test_varint_length_prefix() {
+    let result = Packet::parse_message.parse(LocatingSlice::new(&b"MSG\x03abc"[..]));
+    assert_eq!(result.unwrap(), b"abc".to_vec());
+
+    let result = Packet::parse_message.parse(LocatingSlice::new(&b"MSG\x04abc"[..]));
+    assert!(result.is_err());
+}
@@ -0,0 +1,70 @@
+use winnow::prelude::*;
+use winnow::stream::LocatingSlice;
+use winnow_grammar::error::message;
+use winnow_grammar::grammar;
+use winnow_grammar::report::Report;
+
+grammar! {
+    grammar Assign {
+        pub rule stmt -> (String, i64) =
+            name:target "=" #[expect("an expression")] value:expr ";" -> { (name, value) }
+
+        /// The name of a variable.
+        rule target -> String =
+            name:ident -> { name }
+
+        rule expr -> i64 =
+            "(" e:expr ")" -> { e }
+          | n:i64 -> { n }
+
+        pub rule pair -> (i64, i64) =
+            #[expect("a pair")] p:angled -> { p }
+
+        rule angled -> (i64, i64) =
+            "<" a:i64 "," b:i64 ">" -> { (a, b) }
+    }
+}
+
+#[test]
+fn test_pattern_description() {
+    let input = "x = ;";
+    let err = Assign::parse_stmt
+        .parse(LocatingSlice::new(input))
+        .unwrap_err();
+    assert_eq!(message(&err, input), "expected an expression at 1:5");
+}
+
+#[test]
+fn test_doc_comment_fallback() {
+    let input = "= 1;";
+    let err = Assign::parse_stmt
+        .parse(LocatingSlice::new(input))
+        .unwrap_err();
+    let report = Report::new(&err, input);
+    assert_eq!(
+        report.message(),
+        "expected the name of a variable, found `=`"
+    );
+    assert_eq!(report.rules(), ["stmt", "the name of a variable"]);
+}
+
+#[test]
+fn test_inner_failure_is_kept() {
+    let input = "x = (1;";
+    let err = Assign::parse_stmt
+        .parse(LocatingSlice::new(input))
+        .unwrap_err();
+    assert_eq!(message(&err, input), "expected `)` at 1:7");
+
+    let input = "<1 2>";
+    let err = Assign::parse_pair
+        .parse(LocatingSlice::new(input))
+        .unwrap_err();
+    assert_eq!(message(&err, input), "expected `,` at 1:4");
+
+    let input = "[1, 2]";
+    let err = Assign::parse_pair
+        .parse(LocatingSlice::new(input))
+        .unwrap_err();
+    assert_eq!(message(&err, input), "expected a pair at 1:1");
+}
@@ -0,0 +1,82 @@
+use winnow::prelude::*;
+use winnow::stream::LocatingSlice;
+use winnow_grammar::error::{describe, message};
+use winnow_grammar::grammar;
+
+grammar! {
+    grammar Calc {
+        pub rule binop -> i32 =
+            a:i32 "+" b:i32 -> { a + b }
+          | a:i32 "-" b:i32 -> { a - b }
+          | a:i32 ")" -> { a }
+
+        pub rule assign -> (String, i32) =
+            name:ident "=" value:i32 ";" -> { (name, value) }
+
+        pub rule call -> Vec<String> =
+            f:ident paren(arg:ident) -> { vec![f, arg] }
+
+        pub rule unit -> u32 =
+            n:u32 suffix -> { n }
+
+        rule suffix -> () =
+            "ms" -> { () }
+          | "s" -> { () }
+          | "m" -> { () }
+    }
+}
+
+#[test]
+fn test_expected_one_of() {
+    let input = "1 * 2";
+    let err = Calc::parse_binop
+        .parse(LocatingSlice::new(input))
+        .unwrap_err();
+    assert_eq!(err.offset(), 2);
+    assert_eq!(describe(err.inner()), "expected one of `+`, `-`, `)`");
+    assert_eq!(message(&err, input), "expected one of `+`, `-`, `)` at 1:3");
+}
+
+#[test]
+fn test_furthest_alternative_wins() {
+    // The `+` alternative got past the operator, so its error is reported.
+    let input = "1 + x";
+    let err = Calc::parse_binop
+        .parse(LocatingSlice::new(input))
+        .unwrap_err();
+    assert_eq!(err.offset(), 4);
+    assert_eq!(describe(err.inner()), "expected integer");
+}
+
+#[test]
+fn test_builtin_and_literal_expectations() {
+    let input = "x = 1\ny";
+    let err = Calc::parse_assign
+        .parse(LocatingSlice::new(input))
+        .unwrap_err();
+    assert_eq!(message(&err, input), "expected `;` at 2:1");
+
+    let input = "= 1;";
+    let err = Calc::parse_assign
+        .parse(LocatingSlice::new(input))
+        .unwrap_err();
+    assert_eq!(message(&err, input), "expected identifier at 1:1");
+
+    let input = "f(x";
+    let err = Calc::parse_call
+        .parse(LocatingSlice::new(input))
+        .unwrap_err();
+    assert_eq!(message(&err, input), "expected `)` at 1:4");
+}
+
+#[test]
+fn test_merged_expectations() {
+    let input = "10 h";
+    let err = Calc::parse_unit
+        .parse(LocatingSlice::new(input))
+        .unwrap_err();
+    assert_eq!(
+        message(&err, input),
+        "expected one of `ms`, `s`, `m` at 1:4"
+    );
+}
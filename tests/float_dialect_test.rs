@@ -0,0 +1,145 @@
+use winnow::prelude::*;
+use winnow::stream::LocatingSlice;
+use winnow_grammar::grammar;
+
+grammar! {
+    grammar Dialects {
+        pub rule json -> f64 = f:json_f64 -> { f }
+        pub rule toml -> f64 = f:toml_f64 -> { f }
+        pub rule rust -> f32 = f:rust_f32 -> { f }
+        pub rule hex -> f64 = f:hex_f64 -> { f }
+        pub rule exact -> String = d:json_decimal -> { d }
+
+        pub rule json_pair -> (f64, f64) =
+            "[" a:json_f64 "," b:json_f64 "]" -> { (a, b) }
+    }
+}
+
+grammar! {
+    grammar Prices {
+        pub rule cents -> u32 = d:decimal -> { d }
+
+        // `decimal` is an ordinary rule name; the lossless builtin is `json_decimal`.
+        rule decimal -> u32 = whole:u32 "." frac:u32 -> { whole * 100 + frac }
+    }
+}
+
+fn accepts<T>(
+    parser: fn(&mut LocatingSlice<&'static str>) -> ModalResult<T>,
+    input: &'static str,
+) -> Option<T> {
+    let mut parser = parser;
+    parser.parse(LocatingSlice::new(input)).ok()
+}
+
+#[test]
+fn test_json_numbers() {
+    assert_eq!(accepts(Dialects::parse_json, "0"), Some(0.0));
+    assert_eq!(accepts(Dialects::parse_json, "-12.5e2"), Some(-1250.0));
+    assert_eq!(accepts(Dialects::parse_json, "1E-2"), Some(0.01));
+
+    for input in ["+1", "01", "1.", ".5", "inf", "NaN", "1_000.0", "0x10"] {
+        assert!(
+            accepts(Dialects::parse_json, input).is_none(),
+            "input: {}",
+            input
+        );
+    }
+
+    let result = Dialects::parse_json_pair.parse(LocatingSlice::new("[2.5, -3e1]"));
+    assert_eq!(result.unwrap(), (2.5, -30.0));
+}
+
+#[test]
+fn test_toml_floats() {
+    assert_eq!(accepts(Dialects::parse_toml, "+1.0"), Some(1.0));
+    assert_eq!(
+        accepts(Dialects::parse_toml, "224_617.445_991"),
+        Some(224_617.445_991)
+    );
+    assert_eq!(accepts(Dialects::parse_toml, "5e+22"), Some(5e22));
+    assert_eq!(
+        accepts(Dialects::parse_toml, "-inf"),
+        Some(f64::NEG_INFINITY)
+    );
+    assert!(accepts(Dialects::parse_toml, "nan").unwrap().is_nan());
+
+    // Integers are not floats, and separators must sit between digits.
+    for input in ["42", "1__0.0", "1_.0", "1._0", "01.5", "3.e2", "Inf", "NaN"] {
+        assert!(
+            accepts(Dialects::parse_toml, input).is_none(),
+            "input: {}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_rust_literals() {
+    assert_eq!(accepts(Dialects::parse_rust, "1."), Some(1.0));
+    assert_eq!(accepts(Dialects::parse_rust, "1_000.5"), Some(1000.5));
+    assert_eq!(accepts(Dialects::parse_rust, "2f32"), Some(2.0));
+    assert_eq!(accepts(Dialects::parse_rust, "1e1_0"), Some(1e10));
+    assert_eq!(accepts(Dialects::parse_rust, "-0.5"), Some(-0.5));
+
+    for input in ["1", ".5", "inf", "1.e3"] {
+        assert!(
+            accepts(Dialects::parse_rust, input).is_none(),
+            "input: {}",
+            input
+        );
+    }
+
+    let err = Dialects::parse_rust
+        .parse(LocatingSlice::new("1.5f64"))
+        .unwrap_err();
+    assert_eq!(err.offset(), 3);
+}
+
+#[test]
+fn test_c_hex_floats() {
+    assert_eq!(accepts(Dialects::parse_hex, "0x1.8p3"), Some(12.0));
+    assert_eq!(accepts(Dialects::parse_hex, "-0X.4P-1"), Some(-0.125));
+    assert_eq!(
+        accepts(Dialects::parse_hex, "0x1p-1074"),
+        Some(f64::from_bits(1))
+    );
+    assert_eq!(
+        accepts(Dialects::parse_hex, "0x1.fffffffffffffp1023"),
+        Some(f64::MAX)
+    );
+    assert_eq!(accepts(Dialects::parse_hex, "0x10p0f"), Some(16.0));
+
+    // The binary exponent is mandatory.
+    assert!(accepts(Dialects::parse_hex, "0x1.8").is_none());
+}
+
+#[test]
+fn test_out_of_range_is_an_error() {
+    let err = Dialects::parse_json
+        .parse(LocatingSlice::new("1e400"))
+        .unwrap_err();
+    assert_eq!(err.offset(), 0);
+    assert!(err
+        .inner()
+        .to_string()
+        .contains("a value that fits in `f64`"));
+
+    assert!(accepts(Dialects::parse_hex, "0x1p1024").is_none());
+    assert!(accepts(Dialects::parse_rust, "1e39").is_none());
+}
+
+#[test]
+fn test_lossless_decimal() {
+    let input = "-3.14159265358979323846264338327950288e-10";
+    assert_eq!(
+        accepts(Dialects::parse_exact, input).as_deref(),
+        Some(input)
+    );
+    assert!(accepts(Dialects::parse_exact, "1.").is_none());
+}
+
+#[test]
+fn test_rule_named_decimal() {
+    assert_eq!(accepts(Prices::parse_cents, "12.34"), Some(1234));
+}
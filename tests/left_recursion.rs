@@ -1,3 +1,4 @@
+use winnow::error::ErrMode;
 use winnow::prelude::*;
 use winnow::stream::LocatingSlice;
 use winnow_grammar::grammar;
@@ -32,3 +33,171 @@ fn test_left_recursion() {
         )
     );
 }
+
+grammar! {
+    grammar Ternary {
+        pub rule expr -> u32 =
+            c:expr "?" => a:expr ":" b:expr -> { if c != 0 { a } else { b } }
+          | l:expr "+" r:term -> { l + r }
+          | t:term -> { t }
+
+        rule term -> u32 = n:u32 -> { n }
+    }
+}
+
+#[test]
+fn test_cut_in_continuation() {
+    let result = Ternary::parse_expr.parse(LocatingSlice::new("1 + 1 ? 2 : 3"));
+    assert_eq!(result.unwrap(), 2);
+
+    // After the operator, a missing operand is reported where it is missing
+    // rather than as unexpected input at the operator.
+    let source = "1 ? : 3";
+    let err = Ternary::parse_expr
+        .parse(LocatingSlice::new(source))
+        .unwrap_err();
+    assert_eq!(err.offset(), 4);
+    assert_eq!(
+        winnow_grammar::error::message(&err, source),
+        "expected integer at 1:5"
+    );
+
+    let source = "1 ? 2";
+    let err = Ternary::parse_expr
+        .parse(LocatingSlice::new(source))
+        .unwrap_err();
+    assert_eq!(
+        winnow_grammar::error::message(&err, source),
+        "expected `:` at 1:6"
+    );
+}
+
+// `member` and `call` reach each other in leading position, like the
+// member and call expressions of many language references.
+grammar! {
+    grammar Mutual {
+        pub rule member -> String =
+            c:call "." f:ident -> { format!("{c}.{f}") }
+          | p:primary -> { p }
+
+        pub rule call -> String =
+            m:member "(" ")" -> { format!("{m}()") }
+
+        rule primary -> String = i:ident -> { i }
+    }
+}
+
+#[test]
+fn test_indirect_left_recursion() {
+    let member = |s| Mutual::parse_member.parse(LocatingSlice::new(s));
+    assert_eq!(member("a").unwrap(), "a");
+    assert_eq!(member("a().b").unwrap(), "a().b");
+    assert_eq!(member("a().b().c").unwrap(), "a().b().c");
+    assert!(member("a()").is_err());
+
+    let call = |s| Mutual::parse_call.parse(LocatingSlice::new(s));
+    assert_eq!(call("a()").unwrap(), "a()");
+    assert_eq!(call("a().b()").unwrap(), "a().b()");
+    assert!(call("a").is_err());
+}
+
+// The cut in `call` commits to its closing parenthesis only, not to what
+// `member` expects after the call.
+grammar! {
+    grammar CutMutual {
+        pub rule member -> String =
+            c:call "." f:ident -> { format!("{c}.{f}") }
+          | c:call "!" -> { format!("{c}!") }
+          | i:ident -> { i }
+
+        pub rule call -> String =
+            m:member "(" => ")" -> { format!("{m}()") }
+    }
+}
+
+#[test]
+fn test_cut_in_inlined_rule() {
+    let member = |s| CutMutual::parse_member.parse(LocatingSlice::new(s));
+    assert_eq!(member("a().b").unwrap(), "a().b");
+    assert_eq!(member("a()!").unwrap(), "a()!");
+
+    let mut input = LocatingSlice::new("a(!");
+    assert!(matches!(
+        CutMutual::parse_member(&mut input),
+        Err(ErrMode::Cut(_))
+    ));
+}
+
+// `call` binds the same names as `member`, into which it is inlined.
+grammar! {
+    grammar SameNames {
+        pub rule member -> String =
+            c:call "." f:ident -> { format!("{c}.{f}") }
+          | f:ident -> { f }
+
+        rule call -> String =
+            f:member "(" c:ident ")" -> { format!("{f}({c})") }
+    }
+}
+
+#[test]
+fn test_inlined_rule_binds_same_names() {
+    let member = |s| SameNames::parse_member.parse(LocatingSlice::new(s));
+    assert_eq!(member("a(b).c").unwrap(), "a(b).c");
+    assert_eq!(member("a(b).c(d).e").unwrap(), "a(b).c(d).e");
+}
+
+// A longer cycle, `sum -> diff -> operand -> sum`, with the same binding
+// names in every rule.
+grammar! {
+    grammar Chain {
+        pub rule sum -> i64 =
+            l:diff "+" r:num -> { l + r }
+          | n:num -> { n }
+
+        rule diff -> i64 =
+            l:operand "-" r:num -> { l - r }
+
+        rule operand -> i64 =
+            l:sum -> { l }
+
+        rule num -> i64 = n:i64 -> { n }
+    }
+}
+
+#[test]
+fn test_longer_cycle() {
+    let sum = |s| Chain::parse_sum.parse(LocatingSlice::new(s)).unwrap();
+    assert_eq!(sum("1"), 1);
+    assert_eq!(sum("10 - 2 + 5"), 13);
+    assert_eq!(sum("10 - 2 + 5 - 1 + 1"), 13);
+}
+
+// The recursive call passes the rule's own parameters, and the other
+// calls can pass them on.
+grammar! {
+    grammar Scaled {
+        pub rule sum(scale: i64) -> i64 =
+            l:sum(scale) "+" r:num(scale) -> { l + r }
+          | n:num(scale) -> { n }
+
+        rule num(scale: i64) -> i64 = n:i64 -> { n * scale }
+
+        pub rule list<T: Clone>(item: impl Parser<I, T, winnow::error::ContextError>) -> Vec<T> =
+            l:list(item) "," x:item -> { let mut l = l; l.push(x); l }
+          | x:item -> { vec![x] }
+
+        pub rule numbers -> Vec<u32> = l:list(number) -> { l }
+
+        rule number -> u32 = n:u32 -> { n }
+    }
+}
+
+#[test]
+fn test_left_recursion_with_params() {
+    let mut input = LocatingSlice::new("1 + 2 + 3");
+    assert_eq!(Scaled::parse_sum(&mut input, 10).unwrap(), 60);
+
+    let result = Scaled::parse_numbers.parse(LocatingSlice::new("1, 2, 3"));
+    assert_eq!(result.unwrap(), vec![1, 2, 3]);
+}
@@ -0,0 +1,95 @@
+use winnow::prelude::*;
+use winnow::stream::LocatingSlice;
+use winnow_grammar::grammar;
+
+// Every operator wraps its operands in parentheses, so the output shows
+// how the input was grouped.
+grammar! {
+    grammar Shape {
+        pub rule expr -> String = precedence(atom) {
+            ternary 1: c "?" a ":" b -> { format!("({c} ? {a} : {b})") }
+            nonassoc 2: l "==" r -> { format!("({l} == {r})") }
+            left 3: l "+" r -> { format!("({l} + {r})") }
+            left 3: l "-" r -> { format!("({l} - {r})") }
+            left 4: l "*" r -> { format!("({l} * {r})") }
+            right 5: l "^" r -> { format!("({l} ^ {r})") }
+            prefix 6: "-" x -> { format!("(-{x})") }
+            postfix 7: x "!" -> { format!("({x}!)") }
+        }
+
+        rule atom -> String =
+            n:u32 -> { n.to_string() }
+          | paren(e:expr) -> { e }
+    }
+}
+
+fn shape(input: &str) -> String {
+    Shape::parse_expr.parse(LocatingSlice::new(input)).unwrap()
+}
+
+#[test]
+fn test_infix_associativity() {
+    assert_eq!(shape("1 + 2 - 3"), "((1 + 2) - 3)");
+    assert_eq!(shape("1 + 2 * 3"), "(1 + (2 * 3))");
+    assert_eq!(shape("1 * 2 + 3"), "((1 * 2) + 3)");
+    assert_eq!(shape("2 ^ 3 ^ 4"), "(2 ^ (3 ^ 4))");
+    assert_eq!(shape("(1 + 2) * 3"), "((1 + 2) * 3)");
+}
+
+#[test]
+fn test_prefix_and_postfix() {
+    assert_eq!(shape("-1 + 2"), "((-1) + 2)");
+    assert_eq!(shape("- -1"), "(-(-1))");
+    assert_eq!(shape("-3!"), "(-(3!))");
+    assert_eq!(shape("-2 ^ 2"), "((-2) ^ 2)");
+    assert_eq!(shape("2 * 3!!"), "(2 * ((3!)!))");
+}
+
+#[test]
+fn test_ternary() {
+    assert_eq!(shape("1 ? 2 : 3 + 4"), "(1 ? 2 : (3 + 4))");
+    assert_eq!(shape("1 ? 2 : 3 ? 4 : 5"), "(1 ? 2 : (3 ? 4 : 5))");
+    assert_eq!(shape("1 ? 2 ? 3 : 4 : 5"), "(1 ? (2 ? 3 : 4) : 5)");
+    assert_eq!(shape("1 == 2 ? 3 : 4"), "((1 == 2) ? 3 : 4)");
+}
+
+#[test]
+fn test_nonassoc() {
+    assert_eq!(shape("1 + 1 == 2"), "((1 + 1) == 2)");
+    // A second `==` at the same level is not consumed.
+    let mut input = LocatingSlice::new("1 == 2 == 3");
+    let result = Shape::parse_expr.parse_next(&mut input).unwrap();
+    assert_eq!(result, "(1 == 2)");
+    assert_eq!(*input, " == 3");
+    assert!(Shape::parse_expr
+        .parse(LocatingSlice::new("1 == 2 == 3"))
+        .is_err());
+}
+
+#[test]
+fn test_missing_operand() {
+    assert!(Shape::parse_expr.parse(LocatingSlice::new("1 +")).is_err());
+    assert!(Shape::parse_expr
+        .parse(LocatingSlice::new("1 ? 2"))
+        .is_err());
+}
+
+// The actions compute the value directly.
+grammar! {
+    grammar Calc {
+        pub rule expr -> i64 = precedence(n:i64) {
+            left 1: l "+" r -> { l + r }
+            left 1: l "-" r -> { l - r }
+            left 2: l "*" r -> { l * r }
+            prefix 3: "-" x -> { -x }
+        }
+    }
+}
+
+#[test]
+fn test_evaluate() {
+    let calc = |s| Calc::parse_expr.parse(LocatingSlice::new(s)).unwrap();
+    assert_eq!(calc("1 - 2 - 3"), -4);
+    assert_eq!(calc("2 + 3 * 4 - 1"), 13);
+    assert_eq!(calc("-2 * -3"), 6);
+}
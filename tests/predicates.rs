@@ -0,0 +1,78 @@
+use winnow::prelude::*;
+use winnow::stream::LocatingSlice;
+use winnow_grammar::grammar;
+
+#[derive(Debug, PartialEq)]
+pub enum Number {
+    Digit(u32),
+    Large(u32),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Decl {
+    Typed(String, String),
+    Untyped(String),
+}
+
+grammar! {
+    grammar Guards {
+        pub rule minute -> u32 =
+            n:u32 if n < 60 -> { n }
+
+        pub rule number -> Number =
+            n:u32 if n < 10 -> { Number::Digit(n) }
+          | n:u32           -> { Number::Large(n) }
+
+        // The predicate consults a rule argument, like a typedef table would.
+        pub rule decl(typed: bool) -> Decl =
+            &{ typed } t:ident n:ident -> { Decl::Typed(t, n) }
+          | n:ident                    -> { Decl::Untyped(n) }
+
+        pub rule committed -> u32 =
+            "m" => n:u32 if n < 60 -> { n }
+          | "m" n:u32              -> { n + 1000 }
+    }
+}
+
+#[test]
+fn test_guard() {
+    let result = Guards::parse_minute.parse(LocatingSlice::new("59"));
+    assert_eq!(result.unwrap(), 59);
+
+    let result = Guards::parse_minute.parse(LocatingSlice::new("60"));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_guard_backtracks_to_next_alternative() {
+    let result = Guards::parse_number.parse(LocatingSlice::new("7"));
+    assert_eq!(result.unwrap(), Number::Digit(7));
+
+    let result = Guards::parse_number.parse(LocatingSlice::new("42"));
+    assert_eq!(result.unwrap(), Number::Large(42));
+}
+
+#[test]
+fn test_predicate() {
+    let mut typed = |i: &mut LocatingSlice<&str>| Guards::parse_decl(i, true);
+    let result = typed.parse(LocatingSlice::new("int x"));
+    assert_eq!(
+        result.unwrap(),
+        Decl::Typed("int".to_string(), "x".to_string())
+    );
+
+    // Without the predicate the first alternative would match "int x".
+    let mut input = LocatingSlice::new("int x");
+    let result = Guards::parse_decl(&mut input, false);
+    assert_eq!(result.unwrap(), Decl::Untyped("int".to_string()));
+}
+
+#[test]
+fn test_guard_after_cut() {
+    // The guard is past the cut, so failing it must not fall through.
+    let result = Guards::parse_committed.parse(LocatingSlice::new("m 99"));
+    assert!(result.is_err());
+
+    let result = Guards::parse_committed.parse(LocatingSlice::new("m 5"));
+    assert_eq!(result.unwrap(), 5);
+}
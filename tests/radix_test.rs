@@ -0,0 +1,97 @@
+use winnow::prelude::*;
+use winnow::stream::LocatingSlice;
+use winnow_grammar::grammar;
+
+grammar! {
+    grammar Radix {
+        pub rule color -> u32 = "#" c:hex_u32 -> { c }
+        pub rule mode -> u16 = m:oct_u16 -> { m }
+        pub rule mask -> u8 = m:bin_u8 -> { m }
+
+        pub rule register -> u16 = "reg" r:hex_u16 -> { r }
+
+        pub rule literal -> i32 = n:int_literal::<i32> -> { n }
+        pub rule byte -> u8 = n:int_literal::<u8> -> { n }
+
+        pub rule assign -> (String, u64) =
+            name:ident "=" value:int_literal::<u64> ";" -> { (name, value) }
+    }
+}
+
+#[test]
+fn test_typed_radix_builtins() {
+    let result = Radix::parse_color.parse(LocatingSlice::new("#ff8000"));
+    assert_eq!(result.unwrap(), 0xff8000);
+
+    let result = Radix::parse_mode.parse(LocatingSlice::new("0o755"));
+    assert_eq!(result.unwrap(), 0o755);
+
+    let result = Radix::parse_mode.parse(LocatingSlice::new("644"));
+    assert_eq!(result.unwrap(), 0o644);
+
+    let result = Radix::parse_mask.parse(LocatingSlice::new("0b1010_0101"));
+    assert_eq!(result.unwrap(), 0b1010_0101);
+
+    let result = Radix::parse_mask.parse(LocatingSlice::new("0b102"));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_int_literal_forms() {
+    let cases = [
+        ("42", 42),
+        ("-17", -17),
+        ("1_000_000", 1_000_000),
+        ("0x7fff_ffff", i32::MAX),
+        ("-0x8000_0000", i32::MIN),
+        ("0o777", 0o777),
+        ("0b_1111", 15),
+        ("99i32", 99),
+    ];
+    for (input, expected) in cases {
+        let result = Radix::parse_literal.parse(LocatingSlice::new(input));
+        assert_eq!(result.unwrap(), expected, "input: {}", input);
+    }
+
+    assert!(Radix::parse_literal
+        .parse(LocatingSlice::new("_1"))
+        .is_err());
+    assert!(Radix::parse_literal
+        .parse(LocatingSlice::new("0x"))
+        .is_err());
+    assert!(Radix::parse_byte.parse(LocatingSlice::new("-1")).is_err());
+}
+
+#[test]
+fn test_suffix_must_match() {
+    let result = Radix::parse_byte.parse(LocatingSlice::new("255u8"));
+    assert_eq!(result.unwrap(), 255);
+
+    let err = Radix::parse_byte
+        .parse(LocatingSlice::new("255u16"))
+        .unwrap_err();
+    assert_eq!(err.offset(), 3);
+    assert!(err.inner().to_string().contains("u8"));
+}
+
+#[test]
+fn test_overflow_is_positioned() {
+    let err = Radix::parse_register
+        .parse(LocatingSlice::new("reg 0x1_0000"))
+        .unwrap_err();
+    assert_eq!(err.offset(), 4);
+    assert!(err
+        .inner()
+        .to_string()
+        .contains("a value that fits in `u16`"));
+
+    let err = Radix::parse_byte
+        .parse(LocatingSlice::new("256"))
+        .unwrap_err();
+    assert_eq!(err.offset(), 0);
+
+    let err = Radix::parse_assign
+        .parse(LocatingSlice::new("x = 18_446_744_073_709_551_616;"))
+        .unwrap_err();
+    assert_eq!(err.offset(), 4);
+}
@@ -0,0 +1,72 @@
+use winnow::prelude::*;
+use winnow::stream::LocatingSlice;
+use winnow_grammar::grammar;
+
+grammar! {
+    grammar Ranges {
+        pub rule time -> (u8, u8) =
+            h:u8 in 0..24 ":" m:u8 in 0..=59 -> { (h, m) }
+
+        pub rule temperature -> i32 =
+            t:i32 in -273..=1000 -> { t }
+
+        // Bounds may refer to earlier bindings.
+        pub rule span -> (u32, u32) =
+            start:u32 ".." end:u32 in (start).. -> { (start, end) }
+
+        // A rejected value backtracks, so alternatives can split on ranges.
+        pub rule grade -> &'static str =
+            _s:u32 in 90..=100 -> { "A" }
+          | _s:u32 in 0..90 -> { "B" }
+    }
+}
+
+#[test]
+fn test_ranges() {
+    let result = Ranges::parse_time.parse(LocatingSlice::new("23:59"));
+    assert_eq!(result.unwrap(), (23, 59));
+
+    let err = Ranges::parse_time
+        .parse(LocatingSlice::new("24:00"))
+        .unwrap_err();
+    assert_eq!(err.offset(), 0);
+    assert!(err.inner().to_string().contains("expected value in 0..24"));
+
+    let err = Ranges::parse_time
+        .parse(LocatingSlice::new("12: 75"))
+        .unwrap_err();
+    assert_eq!(err.offset(), 4);
+}
+
+#[test]
+fn test_negative_bounds() {
+    let result = Ranges::parse_temperature.parse(LocatingSlice::new("-40"));
+    assert_eq!(result.unwrap(), -40);
+
+    let result = Ranges::parse_temperature.parse(LocatingSlice::new("-300"));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_bound_from_binding() {
+    let result = Ranges::parse_span.parse(LocatingSlice::new("3..7"));
+    assert_eq!(result.unwrap(), (3, 7));
+
+    let result = Ranges::parse_span.parse(LocatingSlice::new("7..3"));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_range_alternatives() {
+    assert_eq!(
+        Ranges::parse_grade.parse(LocatingSlice::new("95")).unwrap(),
+        "A"
+    );
+    assert_eq!(
+        Ranges::parse_grade.parse(LocatingSlice::new("42")).unwrap(),
+        "B"
+    );
+    assert!(Ranges::parse_grade
+        .parse(LocatingSlice::new("101"))
+        .is_err());
+}
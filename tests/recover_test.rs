@@ -1,6 +1,7 @@
 use winnow::prelude::*;
 use winnow::stream::LocatingSlice;
 use winnow_grammar::grammar;
+use winnow_grammar::recover::Recovered;
 
 grammar! {
     grammar RecoverTest {
@@ -22,3 +23,202 @@ fn test_recovery() {
     assert_eq!(result[2], None);
     assert_eq!(result[3], Some(3));
 }
+
+grammar! {
+    grammar TryRecoverTest {
+        rule item -> i32 = i:i32 ";" -> { i }
+
+        pub rule list -> Vec<Result<i32, Recovered>> =
+            items:try_recover(item, ";")* -> { items }
+    }
+}
+
+#[test]
+fn test_try_recover_keeps_errors() {
+    let source = "1; 2 x; bad; 3;";
+    let result = TryRecoverTest::parse_list
+        .parse(LocatingSlice::new(source))
+        .unwrap();
+
+    assert_eq!(result.len(), 4);
+    assert_eq!(result[0], Ok(1));
+    assert_eq!(result[3], Ok(3));
+
+    let missing = result[1].as_ref().unwrap_err();
+    assert_eq!(missing.skipped, "2 x");
+    assert_eq!(&source[missing.span.clone()], "2 x");
+    assert_eq!(missing.report(source).message(), "expected `;`, found `x`");
+
+    let bad = result[2].as_ref().unwrap_err();
+    assert_eq!(bad.skipped, "bad");
+    assert_eq!(bad.span, 8..11);
+    assert_eq!(bad.report(source).line_col(), (1, 9));
+}
+
+#[test]
+fn test_try_recover_without_sync_fails() {
+    let input = LocatingSlice::new("1; 2");
+    assert!(TryRecoverTest::parse_list.parse(input).is_err());
+}
+
+grammar! {
+    grammar ScanTest {
+        rule stmt -> i32 = "let" i:i32 "end" -> { i }
+
+        rule sync -> () = "end" -> { () }
+
+        pub rule literal_sync -> Vec<Option<i32>> =
+            items:recover(stmt, "end")* -> { items }
+
+        pub rule rule_sync -> Vec<Option<i32>> =
+            items:recover(stmt, sync)* -> { items }
+    }
+}
+
+#[test]
+fn test_recover_scans_large_input() {
+    let source = format!("let 1 end {} end let 2 end", "x".repeat(200_000));
+    let result = ScanTest::parse_literal_sync
+        .parse(LocatingSlice::new(source.as_str()))
+        .unwrap();
+    assert_eq!(result, vec![Some(1), None, Some(2)]);
+}
+
+#[test]
+fn test_recover_literal_and_rule_sync_agree() {
+    let source = "let 1 end let endless end let 3 end";
+    let literal = ScanTest::parse_literal_sync
+        .parse(LocatingSlice::new(source))
+        .unwrap();
+    let rule = ScanTest::parse_rule_sync
+        .parse(LocatingSlice::new(source))
+        .unwrap();
+    assert_eq!(literal, rule);
+    assert_eq!(literal, vec![Some(1), None, None, Some(3)]);
+}
+
+grammar! {
+    grammar BalancedTest {
+        recovery: balanced;
+
+        rule stmt -> i32 =
+            "let" i:i32 ";" -> { i }
+          | "call" "(" args:i32* ")" ";" -> { args.len() as i32 }
+          | "block" b:block -> { b.len() as i32 }
+
+        pub rule block -> Vec<Option<i32>> = { stmts:recover(stmt, ";")* } -> { stmts }
+
+        pub rule program -> Vec<Option<i32>> =
+            stmts:recover(stmt, ";")* -> { stmts }
+    }
+}
+
+#[test]
+fn test_balanced_recovery_skips_nested_sync() {
+    let source = r#"let 1; call(1 { x; y } "a;b"); let 2;"#;
+    let result = BalancedTest::parse_program
+        .parse(LocatingSlice::new(source))
+        .unwrap();
+    assert_eq!(result, vec![Some(1), None, Some(2)]);
+}
+
+#[test]
+fn test_balanced_recovery_stops_at_closer() {
+    let source = "block { let 1; let x } let 2;";
+    let result = BalancedTest::parse_program
+        .parse(LocatingSlice::new(source))
+        .unwrap();
+    // The block holds two statements, the second of them recovered.
+    assert_eq!(result, vec![Some(2), Some(2)]);
+
+    let block = BalancedTest::parse_block
+        .parse(LocatingSlice::new("{ let 1; let x }"))
+        .unwrap();
+    assert_eq!(block, vec![Some(1), None]);
+}
+
+grammar! {
+    grammar CloserTest {
+        rule list -> Vec<i32> = "[" items:i32* "]" -> { items }
+
+        // Without a sync token, recovery ends at the `)` closing the call.
+        rule call -> (String, Option<Vec<i32>>) =
+            name:ident "(" args:recover(list) ")" ";" -> { (name, args) }
+
+        pub rule calls -> Vec<(String, Option<Vec<i32>>)> = c:call* -> { c }
+    }
+}
+
+#[test]
+fn test_recover_to_closer() {
+    let source = "f([1 2]); g([1 x (y)]); h([3]);";
+    let result = CloserTest::parse_calls
+        .parse(LocatingSlice::new(source))
+        .unwrap();
+    assert_eq!(
+        result,
+        vec![
+            ("f".to_string(), Some(vec![1, 2])),
+            ("g".to_string(), None),
+            ("h".to_string(), Some(vec![3])),
+        ]
+    );
+
+    // Nothing to skip before the closer: the call fails as usual.
+    let result = CloserTest::parse_calls.parse(LocatingSlice::new("f();"));
+    assert!(result.is_err());
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Stmt {
+    Let(String, i32),
+    Error(std::ops::Range<usize>, String),
+}
+
+grammar! {
+    grammar ErrorNodeTest {
+        #[on_error(|span, text| Stmt::Error(span, text.to_string()))]
+        rule stmt -> Stmt = "let" name:ident "=" value:i32 ";" -> { Stmt::Let(name, value) }
+
+        pub rule program -> Vec<Stmt> =
+            stmts:recover_node(stmt, ";")* -> { stmts }
+    }
+}
+
+#[test]
+fn test_on_error_builds_error_nodes() {
+    let source = "let a = 1; let = 2; let b = 3;";
+    let result = ErrorNodeTest::parse_program
+        .parse(LocatingSlice::new(source))
+        .unwrap();
+    assert_eq!(
+        result,
+        vec![
+            Stmt::Let("a".to_string(), 1),
+            Stmt::Error(11..18, "let = 2".to_string()),
+            Stmt::Let("b".to_string(), 3),
+        ]
+    );
+}
+
+// Rules named like builtins are called instead of the builtins.
+grammar! {
+    grammar Shadowed {
+        rule try_recover(n: u32) -> u32 = v:u32 -> { v * n }
+
+        rule recover_node(n: u32) -> u32 = v:u32 -> { v + n }
+
+        rule int_literal -> u32 = v:u32 -> { v + 1 }
+
+        pub rule values -> (u32, u32, u32) =
+            a:try_recover(2) b:recover_node(10) c:int_literal -> { (a, b, c) }
+    }
+}
+
+#[test]
+fn test_user_rules_named_like_builtins() {
+    let result = Shadowed::parse_values
+        .parse(LocatingSlice::new("3 4 5"))
+        .unwrap();
+    assert_eq!(result, (6, 14, 6));
+}
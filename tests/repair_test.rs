@@ -0,0 +1,97 @@
+use winnow::prelude::*;
+use winnow::stream::{LocatingSlice, Stateful};
+use winnow_grammar::grammar;
+use winnow_grammar::recover::{Repair, Repairs};
+
+grammar! {
+    grammar Repaired {
+        state: Repairs;
+        repair: true;
+
+        pub rule program -> Vec<(String, i32)> =
+            stmts:stmt* -> { stmts }
+
+        rule stmt -> (String, i32) =
+            "let" => name:ident "=" value:i32 ";" -> { (name, value) }
+    }
+}
+
+grammar! {
+    grammar Tentative {
+        state: Repairs;
+        repair: true;
+
+        pub rule program -> String =
+            (decl "!")? "let" name:ident -> { name }
+
+        rule decl -> String =
+            "let" => name:ident ";" -> { name }
+    }
+}
+
+fn parse(source: &str, budget: usize) -> (Option<Vec<(String, i32)>>, Repairs) {
+    let mut input = Stateful {
+        input: LocatingSlice::new(source),
+        state: Repairs::new(budget),
+    };
+    let result = Repaired::parse_program.parse_next(&mut input).ok();
+    (result, input.state)
+}
+
+#[test]
+fn test_missing_literals_are_inserted() {
+    let (result, repairs) = parse("let a = 1 let b 2;", 4);
+    assert_eq!(
+        result.unwrap(),
+        vec![("a".to_string(), 1), ("b".to_string(), 2)]
+    );
+    assert_eq!(
+        repairs.repairs(),
+        &[
+            Repair::Inserted {
+                literal: ";",
+                at: 9
+            },
+            Repair::Inserted {
+                literal: "=",
+                at: 15
+            },
+        ]
+    );
+    assert_eq!(repairs.remaining(), 2);
+}
+
+#[test]
+fn test_unexpected_token_is_deleted() {
+    let source = "let a = 1 ) ;";
+    let (result, repairs) = parse(source, 4);
+    assert_eq!(result.unwrap(), vec![("a".to_string(), 1)]);
+    let [repair] = repairs.repairs() else {
+        panic!("expected one repair, got {:?}", repairs.repairs());
+    };
+    assert_eq!(repair.message(), "unexpected `)`");
+    assert_eq!(&source[repair.span()], ")");
+
+    let diagnostic = repair.diagnostic(source);
+    assert_eq!(diagnostic.fixes[0].title, "remove `)`");
+    assert_eq!(diagnostic.line_col, (1, 11));
+}
+
+#[test]
+fn test_budget_bounds_repairs() {
+    let (result, repairs) = parse("let a 1 let b 2 let c 3", 2);
+    assert!(result.is_none());
+    assert_eq!(repairs.remaining(), 0);
+}
+
+#[test]
+fn test_backtracked_repairs_are_rolled_back() {
+    let mut input = Stateful {
+        input: LocatingSlice::new("let a"),
+        state: Repairs::new(4),
+    };
+    let result = Tentative::parse_program.parse_next(&mut input);
+    assert_eq!(result.unwrap(), "a");
+    assert!(input.state.repairs().is_empty());
+    assert_eq!(input.state.remaining(), 4);
+}
@@ -0,0 +1,101 @@
+use winnow::error::{ContextError, ParseError};
+use winnow::prelude::*;
+use winnow::stream::LocatingSlice;
+use winnow_grammar::grammar;
+use winnow_grammar::report::Report;
+
+grammar! {
+    grammar Config {
+        pub rule pair -> Vec<(String, i64)> =
+            first:entry second:entry -> { vec![first, second] }
+
+        pub rule entry -> (String, i64) =
+            key:ident "=" value:i64 ";" -> { (key, value) }
+    }
+}
+
+grammar! {
+    grammar Nested {
+        pub rule value -> u32 =
+            "[" v:value "]" -> { v }
+          | n:u32 -> { n }
+    }
+}
+
+fn parse_err(source: &str) -> ParseError<LocatingSlice<&str>, ContextError> {
+    Config::parse_entry
+        .parse(LocatingSlice::new(source))
+        .unwrap_err()
+}
+
+#[test]
+fn test_render_plain() {
+    let source = "port = 80;\nhost = localhost;\n";
+    let err = Config::parse_pair
+        .parse(LocatingSlice::new(source))
+        .unwrap_err();
+    let report = Report::new(&err, source).with_file("server.conf");
+    assert_eq!(report.line_col(), (2, 8));
+    assert_eq!(
+        report.render(),
+        "\
+error: expected integer, found `localhost`
+ --> server.conf:2:8
+  |
+2 | host = localhost;
+  |        ^^^^^^^^^ expected integer
+  |
+  = note: while parsing `pair` > `entry`
+"
+    );
+    assert_eq!(report.to_string(), report.render());
+}
+
+#[test]
+fn test_end_of_input() {
+    let source = "port = 80";
+    let report = Report::new(&parse_err(source), source);
+    assert_eq!(report.message(), "expected `;`, found end of input");
+    assert!(report.render().contains(" --> <input>:1:10\n"));
+    assert!(report
+        .render()
+        .contains("1 | port = 80\n  |          ^ expected `;`\n"));
+}
+
+#[test]
+fn test_caret_alignment() {
+    let source = "\tport = ?;";
+    let report = Report::new(&parse_err(source), source);
+    assert!(report
+        .render()
+        .contains("1 | \tport = ?;\n  | \t       ^ expected integer\n"));
+}
+
+#[test]
+fn test_render_ansi() {
+    let source = "port 80;";
+    let report = Report::new(&parse_err(source), source);
+    let ansi = report.render_ansi();
+    assert!(ansi.starts_with("\x1b[1;31merror\x1b[0m\x1b[1m: expected `=`, found `80`\x1b[0m\n"));
+    assert!(ansi.contains("\x1b[1;31m^^ expected `=`\x1b[0m"));
+    // Without the escape codes the layout is the plain one.
+    let stripped = ansi
+        .replace("\x1b[1;31m", "")
+        .replace("\x1b[1;34m", "")
+        .replace("\x1b[1m", "")
+        .replace("\x1b[0m", "");
+    assert_eq!(stripped, report.render());
+}
+
+#[test]
+fn test_recursive_rules() {
+    let source = "[[x]]";
+    let err = Nested::parse_value
+        .parse(LocatingSlice::new(source))
+        .unwrap_err();
+    let report = Report::new(&err, source);
+    assert_eq!(report.rules(), ["value", "value", "value"]);
+    assert!(report
+        .render()
+        .contains("= note: while parsing `value` (x3)\n"));
+}
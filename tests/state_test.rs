@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+use winnow::prelude::*;
+use winnow::stream::{LocatingSlice, Stateful};
+use winnow_grammar::grammar;
+
+#[derive(Debug, Clone, Default)]
+pub struct Symbols {
+    pub types: HashSet<String>,
+    pub declared: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Stmt {
+    Typedef(String),
+    Decl(String, String),
+    Expr(String),
+}
+
+grammar! {
+    grammar CLike {
+        state: Symbols;
+
+        pub rule stmts -> Vec<Stmt> =
+            s:stmt* -> { s }
+
+        rule stmt -> Stmt =
+            "typedef" t:ident ";" -> { state.types.insert(t.clone()); Stmt::Typedef(t) }
+          | t:type_name n:ident ";" -> { state.declared.push(n.clone()); Stmt::Decl(t, n) }
+          | e:ident ";" -> { Stmt::Expr(e) }
+
+        rule type_name -> String =
+            t:ident if state.types.contains(&t) -> { t }
+    }
+}
+
+grammar! {
+    grammar Rollback {
+        state: Vec<u32>;
+
+        pub rule main -> u32 =
+            n:noted "!" -> { n }
+          | n:noted "?" -> { n + 100 }
+
+        rule noted -> u32 =
+            n:u32 -> { state.push(n); n }
+    }
+}
+
+grammar! {
+    grammar Nested {
+        state: Vec<u32>;
+
+        pub rule optional -> u32 =
+            (a:noted "!")? n:u32 -> { n }
+
+        pub rule repeated -> () =
+            (noted ",")* u32 -> {}
+
+        pub rule group -> () =
+            (noted "!" | noted "?") -> {}
+
+        pub rule recovered -> () =
+            recover((noted "!" ";"), ";") u32 -> {}
+
+        pub rule tried -> () =
+            try_recover((noted "!" ";"), ";") u32 -> {}
+
+        rule noted -> u32 =
+            n:u32 -> { state.push(n); n }
+    }
+}
+
+type Input<'a, S> = Stateful<LocatingSlice<&'a str>, S>;
+
+#[test]
+fn test_typedef_disambiguation() {
+    let input: Input<Symbols> = Stateful {
+        input: LocatingSlice::new("a; typedef a; a b;"),
+        state: Symbols::default(),
+    };
+    let result = CLike::parse_stmts.parse(input).unwrap();
+    assert_eq!(
+        result,
+        vec![
+            Stmt::Expr("a".to_string()),
+            Stmt::Typedef("a".to_string()),
+            Stmt::Decl("a".to_string(), "b".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_state_is_returned_to_caller() {
+    let mut input: Input<Symbols> = Stateful {
+        input: LocatingSlice::new("typedef t; t x; t y;"),
+        state: Symbols::default(),
+    };
+    CLike::parse_stmts.parse_next(&mut input).unwrap();
+    assert_eq!(input.state.declared, vec!["x", "y"]);
+}
+
+#[test]
+fn test_state_restored_on_backtrack() {
+    // The first alternative records `7` and then fails on "!"; the second
+    // alternative must start again from the original state.
+    let mut input: Input<Vec<u32>> = Stateful {
+        input: LocatingSlice::new("7 ?"),
+        state: Vec::new(),
+    };
+    let result = Rollback::parse_main.parse_next(&mut input).unwrap();
+    assert_eq!(result, 107);
+    assert_eq!(input.state, vec![7]);
+}
+
+#[test]
+fn test_state_restored_in_nested_patterns() {
+    let stateful = |text| -> Input<Vec<u32>> {
+        Stateful {
+            input: LocatingSlice::new(text),
+            state: Vec::new(),
+        }
+    };
+
+    // `noted` matches `7` and records it before the optional fails on "!".
+    let mut input = stateful("7");
+    assert_eq!(Nested::parse_optional.parse_next(&mut input).unwrap(), 7);
+    assert!(input.state.is_empty());
+
+    let mut input = stateful("1, 2, 3");
+    Nested::parse_repeated.parse_next(&mut input).unwrap();
+    assert_eq!(input.state, vec![1, 2]);
+
+    let mut input = stateful("5 ?");
+    Nested::parse_group.parse_next(&mut input).unwrap();
+    assert_eq!(input.state, vec![5]);
+
+    let mut input = stateful("4 ; 9");
+    Nested::parse_recovered.parse_next(&mut input).unwrap();
+    assert!(input.state.is_empty());
+
+    let mut input = stateful("4 ; 9");
+    Nested::parse_tried.parse_next(&mut input).unwrap();
+    assert!(input.state.is_empty());
+}
@@ -0,0 +1,80 @@
+use winnow::prelude::*;
+use winnow::stream::LocatingSlice;
+use winnow_grammar::error::suggestion;
+use winnow_grammar::grammar;
+use winnow_grammar::report::Report;
+
+grammar! {
+    grammar Sql {
+        pub rule stmt -> String =
+            "select" c:ident -> { format!("select {}", c) }
+          | "insert" t:ident -> { format!("insert {}", t) }
+          | "delete" t:ident -> { format!("delete {}", t) }
+
+        pub rule order -> bool =
+            "order" "by" c:ident d:direction -> { let _ = c; d }
+
+        rule direction -> bool =
+            "asc" -> { true }
+          | "desc" -> { false }
+    }
+}
+
+fn suggest(source: &str) -> Option<String> {
+    let err = Sql::parse_stmt
+        .parse(LocatingSlice::new(source))
+        .unwrap_err();
+    suggestion(err.inner())
+}
+
+#[test]
+fn test_misspelled_keyword() {
+    assert_eq!(suggest("selct name").as_deref(), Some("select"));
+    assert_eq!(suggest("  isnert users").as_deref(), Some("insert"));
+    assert_eq!(suggest("DELETE users").as_deref(), Some("delete"));
+}
+
+#[test]
+fn test_no_suggestion() {
+    // Too far from every keyword.
+    assert_eq!(suggest("update users"), None);
+    // The keyword matched; the failure is further in.
+    assert_eq!(suggest("select ;"), None);
+}
+
+#[test]
+fn test_nested_keywords() {
+    let source = "order by name dsc";
+    let err = Sql::parse_order
+        .parse(LocatingSlice::new(source))
+        .unwrap_err();
+    assert_eq!(suggestion(err.inner()).as_deref(), Some("desc"));
+
+    let source = "ordr by name";
+    let err = Sql::parse_order
+        .parse(LocatingSlice::new(source))
+        .unwrap_err();
+    assert_eq!(
+        suggestion(err.inner()),
+        None,
+        "a single keyword is not an alternative"
+    );
+}
+
+#[test]
+fn test_report_help() {
+    let source = "selct name";
+    let err = Sql::parse_stmt
+        .parse(LocatingSlice::new(source))
+        .unwrap_err();
+    let report = Report::new(&err, source);
+    assert_eq!(report.suggestion(), Some("select"));
+    assert!(report
+        .render()
+        .ends_with("  = note: while parsing `stmt`\n  = help: did you mean `select`?\n"));
+
+    let diagnostic = Sql::diagnostic(&err, source);
+    assert_eq!(diagnostic.fixes[0].title, "replace with `select`");
+    assert_eq!(diagnostic.fixes[0].new_text, "select");
+    assert_eq!(diagnostic.fixes[0].range, diagnostic.range);
+}
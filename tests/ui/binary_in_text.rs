@@ -0,0 +1,9 @@
+use winnow_grammar::grammar;
+
+grammar! {
+    grammar Text {
+        pub rule length -> u16 = n:be_u16 -> { n }
+    }
+}
+
+fn main() {}
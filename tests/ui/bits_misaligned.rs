@@ -0,0 +1,11 @@
+use winnow_grammar::grammar;
+
+grammar! {
+    grammar Header {
+        input: bytes;
+
+        pub rule flags -> (u8, u8) = bits { a:u3 b:u4 } -> { (a, b) }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+use winnow_grammar::grammar;
+
+// A cut right after the recursive call would commit at every loop iteration.
+grammar! {
+    grammar CutAfterOperand {
+        rule expr -> i32 =
+            l:expr => "+" r:i32 -> { l + r }
+          | n:i32 -> { n }
+    }
+}
+
+// The same cut in a rule inlined into the head of a cycle.
+grammar! {
+    grammar InlinedCutAfterOperand {
+        rule member -> String =
+            c:call "." f:ident -> { format!("{c}.{f}") }
+          | i:ident -> { i }
+
+        rule call -> String =
+            m:member => "(" ")" -> { format!("{m}()") }
+    }
+}
+
+fn main() {}
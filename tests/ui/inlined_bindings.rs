@@ -0,0 +1,15 @@
+use winnow_grammar::grammar;
+
+// `call` is inlined into `member`, but its binding `inner` stays its own.
+grammar! {
+    grammar Inlined {
+        rule member -> String =
+            c:call "." f:ident -> { format!("{c}.{f}{inner}") }
+          | i:ident -> { i }
+
+        rule call -> String =
+            m:member "(" inner:ident ")" -> { format!("{m}({inner})") }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use winnow_grammar::grammar;
+
+grammar! {
+    grammar Unrecovered {
+        #[on_error(|_span, _text| 0)]
+        rule value -> u32 = n:u32 -> { n }
+
+        pub rule values -> Vec<Option<u32>> = vs:recover(value, ";")* -> { vs }
+    }
+}
+
+grammar! {
+    grammar NoBuilder {
+        rule value -> u32 = n:u32 -> { n }
+
+        pub rule values -> Vec<u32> = vs:recover_node(value, ";")* -> { vs }
+    }
+}
+
+fn main() {}
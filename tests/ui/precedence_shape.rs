@@ -0,0 +1,12 @@
+use winnow_grammar::grammar;
+
+// An infix operator needs an operand on each side.
+grammar! {
+    grammar PrecedenceShape {
+        rule expr -> i32 = precedence(n:i32) {
+            left 1: "+" r -> { r }
+        }
+    }
+}
+
+fn main() {}
@@ -1,6 +1,6 @@
 use winnow_grammar::grammar;
 
-// Test case: Indirect Left Recursion (Should Fail Compile)
+// Test case: Indirect Left Recursion without a base case (Should Fail Compile)
 grammar! {
     grammar IndirectRec {
         rule a -> () = b -> { () }
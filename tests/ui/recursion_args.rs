@@ -0,0 +1,12 @@
+use winnow_grammar::grammar;
+
+// The loop continues from the value parsed with the rule's own arguments.
+grammar! {
+    grammar RecursionArgs {
+        rule sum(scale: i64) -> i64 =
+            l:sum(2) "+" r:i64 -> { l + r * scale }
+          | n:i64 -> { n * scale }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use winnow_grammar::grammar;
+
+grammar! {
+    grammar Binary {
+        input: bytes;
+
+        pub rule count -> u32 = n:u32 -> { n }
+
+        pub rule byte -> u8 = b:any -> { b }
+    }
+}
+
+fn main() {}
@@ -4,4 +4,12 @@ fn ui() {
     t.pass("tests/ui/literal_bindings.rs");
     t.compile_fail("tests/ui/ambiguity.rs");
     t.compile_fail("tests/ui/recursion.rs");
+    t.compile_fail("tests/ui/recursion_args.rs");
+    t.compile_fail("tests/ui/cut_after_operand.rs");
+    t.compile_fail("tests/ui/inlined_bindings.rs");
+    t.compile_fail("tests/ui/binary_in_text.rs");
+    t.compile_fail("tests/ui/text_in_binary.rs");
+    t.compile_fail("tests/ui/on_error_misuse.rs");
+    t.compile_fail("tests/ui/bits_misaligned.rs");
+    t.compile_fail("tests/ui/precedence_shape.rs");
 }
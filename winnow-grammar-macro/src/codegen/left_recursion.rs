@@ -0,0 +1,247 @@
+//! Indirect left recursion, such as `a -> b -> a`.
+//!
+//! Rules that call each other in leading position form a cycle. One rule of
+//! each cycle is its *head*. The leading calls from the head to the other
+//! rules of the cycle are inlined, alternative by alternative, until each
+//! alternative starts with the head itself or with something outside the
+//! cycle. The head is then directly left-recursive and compiles to the usual
+//! loop. The other rules are generated unchanged and reach the recursion
+//! through their call to the head.
+//!
+//! An inlined alternative keeps its action. Its leading call stays in the
+//! head's alternative, where it can be inlined further or start the
+//! recursion. The rest of it runs as a step, in a block of its own that ends
+//! with the action, and the block's value is bound to the name the call had.
+//! The caller's action sees the same values as before, and the bindings and
+//! any cut of the inlined alternative end with the block, as they would with
+//! the call. The inlined rule's label is not applied there; its `#[on_error]`
+//! only matters to `recover_node`, which still calls the rule itself.
+
+use proc_macro2::TokenStream;
+use quote::format_ident;
+use std::collections::HashMap;
+use syn_grammar_model::model::{GrammarDefinition, ModelPattern, Rule, RuleVariant};
+
+use crate::extensions::{close, Extensions, Hook};
+
+const ACTION_PREFIX: &str = "__wg_action_";
+const LEAD_PREFIX: &str = "__wg_lead_";
+
+/// The rewritten alternatives of each head, and the actions of the inlined
+/// alternatives.
+#[derive(Default)]
+pub struct Cycles {
+    heads: HashMap<String, Vec<RuleVariant>>,
+    actions: Vec<Inlined>,
+}
+
+/// The action step of an inlined alternative. Its patterns after the leading
+/// call are the arguments of the step.
+pub struct Inlined {
+    pub action: TokenStream,
+    /// The name the leading call had in the inlined rule, and the name it is
+    /// bound to in the head's alternative.
+    pub lead: Option<(syn::Ident, syn::Ident)>,
+}
+
+impl Cycles {
+    /// The alternatives to generate for `rule`, its own unless it is a head.
+    pub fn variants<'a>(&'a self, rule: &'a Rule) -> &'a [RuleVariant] {
+        self.heads
+            .get(&rule.name.to_string())
+            .map_or(&rule.variants, |variants| variants)
+    }
+
+    /// The inlined alternative behind an action step, if `name` is one.
+    pub fn action(&self, name: &syn::Ident) -> Option<&Inlined> {
+        let index: usize = name.to_string().strip_prefix(ACTION_PREFIX)?.parse().ok()?;
+        self.actions.get(index)
+    }
+}
+
+/// Restores the leading calls wrapped in [`Hook::LeftCall`] during lowering.
+pub fn unwrap_left_calls(grammar: &mut GrammarDefinition, ext: &Extensions) {
+    for variant in grammar.rules.iter_mut().flat_map(|r| &mut r.variants) {
+        let Some(ModelPattern::RuleCall {
+            binding,
+            rule_name,
+            args,
+        }) = variant.pattern.first_mut()
+        else {
+            continue;
+        };
+        if !matches!(ext.hook(rule_name), Some(Hook::LeftCall)) {
+            continue;
+        }
+        let (binding, mut call) = (binding.take(), args.remove(0));
+        if let ModelPattern::RuleCall { binding: inner, .. } = &mut call {
+            *inner = binding;
+        }
+        variant.pattern[0] = call;
+    }
+}
+
+/// Finds the cycles of leading calls and rewrites the alternatives of their heads.
+pub fn resolve(grammar: &GrammarDefinition) -> syn::Result<Cycles> {
+    let rules = &grammar.rules;
+    let n = rules.len();
+
+    // reaches[a][b]: `b` can be reached from `a` through leading calls.
+    let mut reaches = vec![vec![false; n]; n];
+    for (caller, rule) in rules.iter().enumerate() {
+        for callee in rule.variants.iter().filter_map(|v| leading_call(rules, v)) {
+            reaches[caller][callee] = true;
+        }
+    }
+    close(&mut reaches);
+
+    let mut cycles = Cycles::default();
+    let mut done = vec![false; n];
+    for first in 0..n {
+        if done[first] {
+            continue;
+        }
+        let members: Vec<usize> = (0..n)
+            .filter(|&r| r == first || (reaches[first][r] && reaches[r][first]))
+            .collect();
+        for &member in &members {
+            done[member] = true;
+        }
+        if members.len() < 2 {
+            continue;
+        }
+
+        let head = members
+            .iter()
+            .copied()
+            .find(|&head| is_acyclic(rules, &members, head))
+            .ok_or_else(|| {
+                let names: Vec<String> = members
+                    .iter()
+                    .map(|&m| format!("`{}`", rules[m].name))
+                    .collect();
+                syn::Error::new(
+                    rules[first].name.span(),
+                    format!(
+                        "left recursion between {} has no rule that every cycle passes through",
+                        names.join(", ")
+                    ),
+                )
+            })?;
+
+        for &member in members.iter().filter(|&&m| m != head) {
+            let rule = &rules[member];
+            if !rule.params.is_empty() || !rule.generics.params.is_empty() {
+                return Err(syn::Error::new(
+                    rule.name.span(),
+                    format!(
+                        "`{}` is inlined into the left recursion of `{}` and cannot take parameters",
+                        rule.name, rules[head].name
+                    ),
+                ));
+            }
+        }
+
+        let mut variants = Vec::new();
+        for variant in &rules[head].variants {
+            inline(
+                rules,
+                &members,
+                head,
+                variant.clone(),
+                &mut cycles.actions,
+                &mut variants,
+            );
+        }
+        cycles.heads.insert(rules[head].name.to_string(), variants);
+    }
+    Ok(cycles)
+}
+
+/// The rule called at the start of `variant`, if any.
+fn leading_call(rules: &[Rule], variant: &RuleVariant) -> Option<usize> {
+    match variant.pattern.first() {
+        Some(ModelPattern::RuleCall { rule_name, .. }) => {
+            rules.iter().position(|r| &r.name == rule_name)
+        }
+        _ => None,
+    }
+}
+
+/// Whether the leading calls among `members` other than `head` have no cycle,
+/// counting a rule calling itself.
+fn is_acyclic(rules: &[Rule], members: &[usize], head: usize) -> bool {
+    let n = rules.len();
+    let mut reaches = vec![vec![false; n]; n];
+    for &caller in members.iter().filter(|&&m| m != head) {
+        for callee in rules[caller]
+            .variants
+            .iter()
+            .filter_map(|v| leading_call(rules, v))
+        {
+            if callee != head && members.contains(&callee) {
+                reaches[caller][callee] = true;
+            }
+        }
+    }
+    close(&mut reaches);
+    (0..n).all(|r| !reaches[r][r])
+}
+
+/// Replaces a leading call to a member other than the head with each
+/// alternative of the callee: its leading call to a member, then a step
+/// running the rest of it and its action.
+fn inline(
+    rules: &[Rule],
+    members: &[usize],
+    head: usize,
+    variant: RuleVariant,
+    actions: &mut Vec<Inlined>,
+    out: &mut Vec<RuleVariant>,
+) {
+    let callee = leading_call(rules, &variant).filter(|&c| c != head && members.contains(&c));
+    let (
+        Some(callee),
+        Some(ModelPattern::RuleCall {
+            binding, rule_name, ..
+        }),
+    ) = (callee, variant.pattern.first())
+    else {
+        out.push(variant);
+        return;
+    };
+
+    for inlined in &rules[callee].variants {
+        let index = actions.len();
+        let mut rest = inlined.pattern.clone();
+        let mut pattern = Vec::new();
+        let mut lead = None;
+        if leading_call(rules, inlined).is_some_and(|c| members.contains(&c)) {
+            let mut call = rest.remove(0);
+            if let ModelPattern::RuleCall {
+                binding: Some(name),
+                ..
+            } = &mut call
+            {
+                let fresh = format_ident!("{}{}", LEAD_PREFIX, index, span = name.span());
+                lead = Some((std::mem::replace(name, fresh.clone()), fresh));
+            }
+            pattern.push(call);
+        }
+        pattern.push(ModelPattern::RuleCall {
+            binding: binding.clone(),
+            rule_name: format_ident!("{}{}", ACTION_PREFIX, index, span = rule_name.span()),
+            args: rest,
+        });
+        actions.push(Inlined {
+            action: inlined.action.clone(),
+            lead,
+        });
+        pattern.extend(variant.pattern[1..].iter().cloned());
+        let expanded = RuleVariant {
+            pattern,
+            action: variant.action.clone(),
+        };
+        inline(rules, members, head, expanded, actions, out);
+    }
+}
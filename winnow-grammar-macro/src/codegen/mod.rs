@@ -1,38 +1,79 @@
 use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote, quote_spanned};
+use std::cell::{Cell, RefCell};
 use std::collections::HashSet;
 use syn_grammar_model::{
     analysis,
     model::{GrammarDefinition, ModelPattern, Rule, RuleVariant},
 };
 
-pub fn generate_rust(grammar: GrammarDefinition) -> syn::Result<TokenStream> {
-    let mut codegen = Codegen::new(&grammar);
+use crate::extensions::{BitField, Extensions, Fixity, Hook, InputKind, Operator, RecoveryKind};
+
+mod left_recursion;
+
+use left_recursion::Cycles;
+
+pub fn generate_rust(mut grammar: GrammarDefinition, ext: &Extensions) -> syn::Result<TokenStream> {
+    left_recursion::unwrap_left_calls(&mut grammar, ext);
+    let cycles = left_recursion::resolve(&grammar)?;
+    let mut codegen = Codegen::new(&grammar, ext, cycles);
     codegen.generate()
 }
 
 struct Codegen<'a> {
     grammar: &'a GrammarDefinition,
+    ext: &'a Extensions,
     user_rules: HashSet<String>,
+    /// The error type of every generated parser.
+    error: TokenStream,
+    /// Rules rewritten to resolve indirect left recursion.
+    cycles: Cycles,
+    /// The typed parameters of the rule being generated. Passed on as
+    /// arguments, they are values rather than parsers.
+    value_params: RefCell<Vec<syn::Ident>>,
+    /// Whether the pattern being generated is inside a group, option or
+    /// repetition that scopes its cuts with `uncut`.
+    cut_scope: Cell<bool>,
 }
 
 impl<'a> Codegen<'a> {
-    fn new(grammar: &'a GrammarDefinition) -> Self {
-        let user_rules = grammar.rules.iter().map(|r| r.name.to_string()).collect();
+    fn new(grammar: &'a GrammarDefinition, ext: &'a Extensions, cycles: Cycles) -> Self {
+        // Synthetic hook rules are expanded inline and never generated as functions.
+        let user_rules = grammar
+            .rules
+            .iter()
+            .filter(|r| ext.hook(&r.name).is_none())
+            .map(|r| r.name.to_string())
+            .collect();
+        let error = match &ext.error {
+            Some(ty) => quote! { #ty },
+            None => quote! { ::winnow::error::ContextError },
+        };
         Self {
             grammar,
+            ext,
             user_rules,
+            error,
+            cycles,
+            value_params: RefCell::default(),
+            cut_scope: Cell::new(false),
         }
     }
 
     fn generate(&mut self) -> syn::Result<TokenStream> {
         let grammar_name = &self.grammar.name;
         let span = Span::mixed_site();
+        let error = &self.error;
         let use_statements = &self.grammar.uses;
 
         let has_user_ws = self.user_rules.contains("ws");
 
-        let rules = self.grammar.rules.iter().map(|r| self.generate_rule(r));
+        let rules = self
+            .grammar
+            .rules
+            .iter()
+            .filter(|r| self.user_rules.contains(&r.name.to_string()))
+            .map(|r| self.generate_rule(r));
 
         let use_super = quote_spanned! {Span::call_site()=> use super::*; };
 
@@ -41,20 +82,49 @@ impl<'a> Codegen<'a> {
                 #[allow(unused_imports)]
                 use parse_ws as ws;
             }
+        } else if self.ext.input == InputKind::Bytes {
+            quote_spanned! {span=>
+                // Binary input has no implicit whitespace
+                #[allow(dead_code)]
+                fn ws<I>(_: &mut I) -> ::winnow::ModalResult<(), #error>
+                where
+                    I: ::winnow::stream::Stream,
+                {
+                    Ok(())
+                }
+            }
         } else {
             quote_spanned! {span=>
                 // Whitespace handling (similar to syn)
                 #[allow(dead_code)]
-                fn ws<I>(input: &mut I) -> ::winnow::ModalResult<()>
+                fn ws<I>(input: &mut I) -> ::winnow::ModalResult<(), #error>
                 where
                     I: ::winnow::stream::Stream<Token = char> + ::winnow::stream::StreamIsPartial + for<'a> ::winnow::stream::Compare<&'a str>,
                     <I as ::winnow::stream::Stream>::Slice: ::winnow::stream::AsBStr,
+                    #error: ::winnow::error::ParserError<I>,
                 {
                     ::winnow::ascii::multispace0.parse_next(input).map(|_| ())
                 }
             }
         };
 
+        // Text grammars can resolve their errors against the source.
+        let diagnostic_fn = if self.ext.input == InputKind::Chars && self.ext.error.is_none() {
+            let grammar_name_str = grammar_name.to_string();
+            quote_spanned! {span=>
+                /// Converts a parse error into an LSP-compatible diagnostic.
+                pub fn diagnostic<I>(
+                    error: &::winnow::error::ParseError<I, ::winnow::error::ContextError>,
+                    source: &str,
+                ) -> ::winnow_grammar::diagnostic::Diagnostic {
+                    ::winnow_grammar::diagnostic::Diagnostic::new(error, source)
+                        .with_source(#grammar_name_str)
+                }
+            }
+        } else {
+            quote! {}
+        };
+
         Ok(quote_spanned! {span=>
             #[allow(non_snake_case)]
             pub mod #grammar_name {
@@ -73,6 +143,8 @@ impl<'a> Codegen<'a> {
 
                 #ws_parser
 
+                #diagnostic_fn
+
                 #(#rules)*
             }
         })
@@ -83,11 +155,18 @@ impl<'a> Codegen<'a> {
         let rule_name_str = rule_name.to_string();
         let is_ws_rule = rule_name_str == "ws";
         let span = Span::mixed_site();
+        let error = &self.error;
         let fn_name = format_ident!("parse_{}", rule_name, span = span);
         let ret_type = &rule.return_type;
 
         let mut extra_generics = Vec::new();
         let mut params_tokens = Vec::new();
+        *self.value_params.borrow_mut() = rule
+            .params
+            .iter()
+            .filter(|(_, ty)| ty.is_some())
+            .map(|(name, _)| name.clone())
+            .collect();
 
         for (name, ty) in &rule.params {
             match ty {
@@ -95,23 +174,24 @@ impl<'a> Codegen<'a> {
                 None => {
                     let output_type = format_ident!("Output_{}", name, span = Span::mixed_site());
                     extra_generics.push(output_type.clone());
-                    // We assume standard ContextError. If user wants custom error, they should provide explicit type.
+                    // Parser arguments share the grammar's error type.
+                    let error = &self.error;
                     params_tokens.push(quote! {
-                        mut #name: impl ::winnow::Parser<I, #output_type, ::winnow::error::ContextError>
+                        mut #name: impl ::winnow::Parser<I, #output_type, #error>
                     });
                 }
             }
         }
 
-        let (recursive_refs, base_refs) =
-            analysis::split_left_recursive(&rule.name, &rule.variants);
+        let variants = self.cycles.variants(rule);
+        let (recursive_refs, base_refs) = analysis::split_left_recursive(&rule.name, variants);
 
         let lhs_ident = format_ident!("lhs", span = span);
 
         let body = if recursive_refs.is_empty() {
-            self.generate_variants_body(&rule.variants, ret_type)
+            self.generate_variants_body(variants, ret_type)
         } else if base_refs.is_empty() {
-            quote_spanned! {span=>
+            quote_spanned! {rule_name.span()=>
                 compile_error!("Left-recursive rule requires at least one non-recursive base variant.")
             }
         } else {
@@ -119,8 +199,7 @@ impl<'a> Codegen<'a> {
             let recursive_owned: Vec<RuleVariant> = recursive_refs.into_iter().cloned().collect();
 
             let base_parser = self.generate_variants_body(&base_owned, ret_type);
-            let loop_body =
-                self.generate_recursive_loop_body(&recursive_owned, ret_type, &lhs_ident);
+            let loop_body = self.generate_recursive_loop_body(rule, &recursive_owned, &lhs_ident);
 
             quote_spanned! {span=>
                 let mut #lhs_ident = #base_parser?;
@@ -132,6 +211,23 @@ impl<'a> Codegen<'a> {
             }
         };
 
+        // A described rule is named by its description, and a failure at its
+        // first token expects the description instead of the tokens tried.
+        let (label, body) = match self.ext.label(rule_name) {
+            Some(label) => (
+                label.to_string(),
+                quote_spanned! {span=>
+                    ::winnow_grammar::error::expect(
+                        input,
+                        #label,
+                        ws,
+                        |input: &mut I| -> ::winnow::ModalResult<#ret_type, #error> { #body },
+                    )
+                },
+            ),
+            None => (rule_name_str.clone(), body),
+        };
+
         // Check rule visibility
         let vis = if rule.is_pub {
             quote! { pub }
@@ -161,10 +257,31 @@ impl<'a> Codegen<'a> {
             quote! {}
         };
 
+        let state_preds = match &self.ext.state {
+            Some(state_ty) => quote_spanned! {span=>
+                I: ::winnow_grammar::state::StateStream<State = #state_ty>,
+                #state_ty: ::std::clone::Clone,
+            },
+            None => quote! {},
+        };
+
+        let (token_ty, input_preds, slice_preds) = match self.ext.input {
+            InputKind::Chars => (
+                quote_spanned! {span=> char },
+                quote_spanned! {span=> + ::winnow::stream::Compare<char> + ::winnow::stream::FindSlice<char> },
+                quote_spanned! {span=> AsRef<str> + std::fmt::Display },
+            ),
+            InputKind::Bytes => (
+                quote_spanned! {span=> u8 },
+                quote_spanned! {span=> + for<'a> ::winnow::stream::Compare<&'a [u8]> + Clone },
+                quote_spanned! {span=> AsRef<[u8]> },
+            ),
+        };
+
         let ws_shadow = if is_ws_rule {
             quote_spanned! {span=>
                 #[allow(dead_code)]
-                fn ws<I>(_: &mut I) -> ::winnow::ModalResult<()>
+                fn ws<I>(_: &mut I) -> ::winnow::ModalResult<(), #error>
                 where
                     I: ::winnow::stream::Stream,
                 {
@@ -176,32 +293,34 @@ impl<'a> Codegen<'a> {
         };
 
         quote_spanned! {span=>
-            #vis fn #fn_name<#gen_params #comma1 I #comma2 #(#extra_generics),* >(input: &mut I, #(#params_tokens),*) -> ::winnow::ModalResult<#ret_type>
+            #vis fn #fn_name<#gen_params #comma1 I #comma2 #(#extra_generics),* >(input: &mut I, #(#params_tokens),*) -> ::winnow::ModalResult<#ret_type, #error>
             where
                 #where_preds
-                I: ::winnow::stream::Stream<Token = char>
+                #state_preds
+                I: ::winnow::stream::Stream<Token = #token_ty>
                    + ::winnow::stream::StreamIsPartial
                    + ::winnow::stream::Location
-                   + ::winnow::stream::Compare<char>
                    + for<'a> ::winnow::stream::Compare<&'a str>
                    // Extra bounds required by some built-in parsers (like float)
                    + ::winnow::stream::Compare<::winnow::ascii::Caseless<&'static str>>
                    + ::winnow::stream::AsBStr
                    // Required for recover which uses find_slice
-                   + ::winnow::stream::FindSlice<char>
-                   + ::winnow::stream::FindSlice<&'static str>,
-                <I as ::winnow::stream::Stream>::Slice: ::winnow::stream::AsBStr + AsRef<str> + std::fmt::Display + ::winnow::stream::ParseSlice<f64> + ::winnow::stream::ParseSlice<f32>,
+                   + ::winnow::stream::FindSlice<&'static str>
+                   #input_preds,
+                <I as ::winnow::stream::Stream>::Slice: ::winnow::stream::AsBStr + #slice_preds + ::winnow::stream::ParseSlice<f64> + ::winnow::stream::ParseSlice<f32>,
                 <I as ::winnow::stream::Stream>::IterOffsets: Clone,
+                #error: ::winnow::error::ParserError<I>
+                    + ::winnow::error::AddContext<I, ::winnow::error::StrContext>,
             {
                 use ::winnow::Parser;
                 use ::winnow::error::ContextError;
 
                 #ws_shadow
 
-                (|input: &mut I| -> ::winnow::ModalResult<#ret_type> {
+                (|input: &mut I| -> ::winnow::ModalResult<#ret_type, #error> {
                     #body
                 })
-                .context(::winnow::error::StrContext::Label(#rule_name_str))
+                .context(::winnow::error::StrContext::Label(#label))
                 .parse_next(input)
             }
         }
@@ -213,43 +332,83 @@ impl<'a> Codegen<'a> {
         ret_type: &syn::Type,
     ) -> TokenStream {
         let span = Span::mixed_site();
+        let error = &self.error;
         let variant_parsers = variants.iter().map(|v| {
-            let steps = self.generate_sequence_steps(&v.pattern, false);
-            let action = &v.action;
+            let body = self.generate_variant_body(v, ret_type);
             quote_spanned! {span=>
-                |input: &mut I| -> ::winnow::ModalResult<#ret_type> {
-                    #steps
-                    Ok({ #action })
-                }
+                (|input: &mut I| -> ::winnow::ModalResult<#ret_type, #error> {
+                    #body
+                })(input)
             }
         });
 
         if variants.len() == 1 {
-            let v = &variants[0];
-            let steps = self.generate_sequence_steps(&v.pattern, false);
-            let action = &v.action;
+            let body = self.generate_variant_body(&variants[0], ret_type);
             quote_spanned! {span=>
                 {
-                    #steps
-                    Ok({ #action })
+                    #body
                 }
             }
         } else {
-            quote_spanned! {span=>
-                alt((
-                    #(#variant_parsers),*
-                )).parse_next(input)
+            let keywords = variants.iter().filter_map(|v| leading_keyword(&v.pattern));
+            self.generate_alternatives(variant_parsers, keywords)
+        }
+    }
+
+    fn generate_variant_body(&self, variant: &RuleVariant, ret_type: &syn::Type) -> TokenStream {
+        let span = Span::mixed_site();
+        let error = &self.error;
+        let steps = self.generate_sequence_steps(&variant.pattern, false);
+        let action = self.generate_action(&variant.action);
+
+        if self.ext.state.is_none() {
+            return quote_spanned! {span=>
+                #steps
+                Ok(#action)
+            };
+        }
+
+        // Roll the user state back together with the input when the variant backtracks.
+        quote_spanned! {span=>
+            let state_checkpoint = ::std::clone::Clone::clone(::winnow_grammar::state::StateStream::state(input));
+            let result = (|| -> ::winnow::ModalResult<#ret_type, #error> {
+                #steps
+                Ok(#action)
+            })();
+            if let Err(::winnow::error::ErrMode::Backtrack(_)) = &result {
+                *::winnow_grammar::state::StateStream::state_mut(input) = state_checkpoint;
+            }
+            result
+        }
+    }
+
+    /// Wraps an action block, binding the reserved `state` name when the grammar declares one.
+    fn generate_action(&self, action: &TokenStream) -> TokenStream {
+        let span = Span::mixed_site();
+        match &self.ext.state {
+            Some(_) => {
+                let state = format_ident!("state", span = Span::call_site());
+                quote_spanned! {span=>
+                    {
+                        #[allow(unused_variables)]
+                        let #state = ::winnow_grammar::state::StateStream::state_mut(input);
+                        #action
+                    }
+                }
             }
+            None => quote_spanned! {span=> { #action } },
         }
     }
 
     fn generate_recursive_loop_body(
         &self,
+        rule: &Rule,
         variants: &[RuleVariant],
-        ret_type: &syn::Type,
         lhs_ident: &syn::Ident,
     ) -> TokenStream {
         let span = Span::mixed_site();
+        let ret_type = &rule.return_type;
+        let error = &self.error;
 
         let arms = variants.iter().map(|v| {
             let lhs_binding = match &v.pattern[0] {
@@ -266,21 +425,62 @@ impl<'a> Codegen<'a> {
             };
 
             // Recursion always consumes the first pattern (the recursive call).
-            // We check if that first pattern was 'cut'. If so, the rest is cut.
-            // But ModelPattern::RuleCall cannot be a Cut itself.
-            // However, if the pattern list is `[Recurse, Cut, ...]`, then `patterns` below starts with `Cut`.
+            // A cut in the rest commits to this continuation: its errors end
+            // the loop and reach the caller. A cut right after the recursive
+            // call would commit before anything of the continuation matched,
+            // so the loop could never end.
+            // The cut may also start the rest of an inlined alternative.
+            let after_lhs = match v.pattern.get(1) {
+                Some(ModelPattern::RuleCall { rule_name, args, .. })
+                    if self.cycles.action(rule_name).is_some() =>
+                {
+                    args.first()
+                }
+                next => next,
+            };
+            if let (Some(ModelPattern::Cut(_)), ModelPattern::RuleCall { rule_name, .. }) =
+                (after_lhs, &v.pattern[0])
+            {
+                return quote_spanned! {rule_name.span()=>
+                    compile_error!("a cut directly after the left operand commits to this continuation before it matches anything; place the cut after the operator");
+                };
+            }
+
+            // The loop continues from the value already parsed, which is only
+            // the value of the recursive call if it has the same arguments.
+            if let ModelPattern::RuleCall { rule_name, args, .. } = &v.pattern[0] {
+                if !forwards_params(args, &rule.params) {
+                    let names = rule.params.iter().map(|(name, _)| name.to_string());
+                    let message = format!(
+                        "a left-recursive call must pass the rule's own parameters unchanged: `{}({})`",
+                        rule.name,
+                        names.collect::<Vec<_>>().join(", ")
+                    );
+                    return quote_spanned! {rule_name.span()=> compile_error!(#message); };
+                }
+            }
 
             let patterns = &v.pattern[1..];
             let steps = self.generate_sequence_steps(patterns, false);
-            let action = &v.action;
+            let action = self.generate_action(&v.action);
+
+            let (save_state, restore_state) = if self.ext.state.is_some() {
+                (
+                    quote_spanned! {span=> let state_checkpoint = ::std::clone::Clone::clone(::winnow_grammar::state::StateStream::state(input)); },
+                    quote_spanned! {span=> *::winnow_grammar::state::StateStream::state_mut(input) = state_checkpoint; },
+                )
+            } else {
+                (quote! {}, quote! {})
+            };
 
             quote_spanned! {span=>
                 {
                     let checkpoint = ::winnow::stream::Stream::checkpoint(input);
-                    let attempt = (|| -> ::winnow::ModalResult<#ret_type> {
-                        #steps
+                    #save_state
+                    let attempt = (|| -> ::winnow::ModalResult<#ret_type, #error> {
                         #bind_lhs
-                        Ok({ #action })
+                        #steps
+                        Ok(#action)
                     })();
 
                     match attempt {
@@ -293,6 +493,7 @@ impl<'a> Codegen<'a> {
                                 // If it's a backtrack error, we reset and try next variant.
                                 ::winnow::error::ErrMode::Backtrack(_) => {
                                     ::winnow::stream::Stream::reset(input, &checkpoint);
+                                    #restore_state
                                 }
                                 // If it's Cut or Incomplete, we propagate.
                                 _ => return Err(e),
@@ -316,6 +517,7 @@ impl<'a> Codegen<'a> {
                 continue;
             }
             steps.push(self.generate_step(p, in_cut));
+            in_cut |= contains_cut(p);
         }
         quote! { #(#steps)* }
     }
@@ -323,6 +525,29 @@ impl<'a> Codegen<'a> {
     fn generate_step(&self, pattern: &ModelPattern, in_cut: bool) -> TokenStream {
         let span = Span::mixed_site();
 
+        // The rest of an alternative inlined to resolve indirect left
+        // recursion, and its action. Its bindings and cut end with the block.
+        if let ModelPattern::RuleCall {
+            binding,
+            rule_name,
+            args,
+        } = pattern
+        {
+            if let Some(inlined) = self.cycles.action(rule_name) {
+                let lead = inlined
+                    .lead
+                    .as_ref()
+                    .map(|(name, fresh)| quote_spanned! {span=> let #name = #fresh; });
+                let steps = self.generate_sequence_steps(args, false);
+                let action = self.generate_action(&inlined.action);
+                let value = quote_spanned! {span=> { #lead #steps #action } };
+                return match binding {
+                    Some(name) => quote_spanned! {span=> let #name = #value; },
+                    None => quote_spanned! {span=> let _ = #value; },
+                };
+            }
+        }
+
         // Special case: Unwrap groups to allow bindings to escape to the current scope.
         // But only if they are simple sequences. If they are alts, generate_parser_expr handles them (returning a value).
         if let ModelPattern::Group(alts, _) = pattern {
@@ -353,15 +578,41 @@ impl<'a> Codegen<'a> {
             ModelPattern::Not(_, _) => {
                 // Not should not produce bindings.
             }
+            ModelPattern::RuleCall {
+                rule_name,
+                binding: None,
+                ..
+            } => {
+                // Statement-like hooks are expanded in place; the rest are parsers.
+                if let Some(step) = self
+                    .ext
+                    .hook(rule_name)
+                    .and_then(|hook| self.generate_hook_step(hook, in_cut))
+                {
+                    return step;
+                }
+            }
             _ => {}
         }
 
         // Default: Generate a parser expression and run it.
         let parser_expr = self.generate_parser_expr(pattern);
 
+        // After a cut, a grammar with `repair: true;` repairs missing
+        // literals instead of failing.
+        if let (Some(literal), None, true) = (
+            literal_text(pattern),
+            get_inner_binding(pattern),
+            in_cut && self.ext.repair.is_some(),
+        ) {
+            return quote_spanned! {span=>
+                ::winnow_grammar::recover::repair(input, #literal, #parser_expr)?;
+            };
+        }
+
         // If we are in cut mode, wrap the parser.
         let parser_expr = if in_cut {
-            quote_spanned! {span=> ::winnow::combinator::cut_err(#parser_expr) }
+            self.cut(parser_expr)
         } else {
             parser_expr
         };
@@ -405,6 +656,433 @@ impl<'a> Codegen<'a> {
         }
     }
 
+    fn generate_hook_step(&self, hook: &Hook, in_cut: bool) -> Option<TokenStream> {
+        let span = Span::mixed_site();
+        match hook {
+            Hook::Predicate(cond) => {
+                // A failed predicate backtracks like any other parser, unless we are past a cut.
+                let err = if in_cut {
+                    quote_spanned! {span=> ::winnow::error::ErrMode::Cut(::winnow::error::ParserError::from_input(input)) }
+                } else {
+                    quote_spanned! {span=> ::winnow::error::ErrMode::Backtrack(::winnow::error::ParserError::from_input(input)) }
+                };
+                let bind_state = match &self.ext.state {
+                    Some(_) => {
+                        let state = format_ident!("state", span = Span::call_site());
+                        quote_spanned! {span=>
+                            #[allow(unused_variables)]
+                            let #state = ::winnow_grammar::state::StateStream::state_mut(input);
+                        }
+                    }
+                    None => quote! {},
+                };
+                Some(quote_spanned! {span=>
+                    let holds: bool = {
+                        #bind_state
+                        #cond
+                    };
+                    if !holds {
+                        return Err(#err);
+                    }
+                })
+            }
+            Hook::Bits(fields, _) => {
+                // Bind each named field in the enclosing scope.
+                let names = fields.iter().flat_map(|f| &f.name);
+                let parser = self.generate_hook_parser(hook, &[]);
+                let parser = if in_cut { self.cut(parser) } else { parser };
+                Some(quote_spanned! {span=>
+                    let (#(#names,)*) = #parser.parse_next(input)?;
+                })
+            }
+            Hook::BackRef(_)
+            | Hook::Take(_)
+            | Hook::Count(_)
+            | Hook::InRange(_)
+            | Hook::IntLiteral(_)
+            | Hook::Expect(_)
+            | Hook::TryRecover
+            | Hook::RecoverNode(_)
+            | Hook::Closer
+            | Hook::Precedence(..)
+            | Hook::LeftCall => None,
+        }
+    }
+
+    fn generate_hook_parser(&self, hook: &Hook, args: &[ModelPattern]) -> TokenStream {
+        let span = Span::mixed_site();
+        let error = &self.error;
+        match hook {
+            Hook::Take(count) => {
+                // Raw data: no whitespace is skipped before it.
+                let count = self.generate_count(count);
+                let take = match self.ext.input {
+                    // Length prefixes count bytes, so text is taken by byte
+                    // offset, which must fall between two characters.
+                    InputKind::Chars => quote_spanned! {span=>
+                        let on_boundary = ::winnow::stream::Stream::eof_offset(input) == count
+                            || ::winnow::stream::Stream::iter_offsets(input)
+                                .map(|(offset, _)| offset)
+                                .take_while(|&offset| offset <= count)
+                                .any(|offset| offset == count);
+                        if !on_boundary {
+                            return Err(::winnow::error::ErrMode::Backtrack(
+                                ::winnow::error::ParserError::from_input(input),
+                            ));
+                        }
+                        Ok(::winnow::stream::Stream::next_slice(input, count))
+                    },
+                    InputKind::Bytes => quote_spanned! {span=>
+                        ::winnow::token::take(count).parse_next(input)
+                    },
+                };
+                quote_spanned! {span=>
+                    (|input: &mut I| -> ::winnow::ModalResult<<I as ::winnow::stream::Stream>::Slice, #error> {
+                        #count
+                        #take
+                    })
+                }
+            }
+            Hook::Count(count) => {
+                let count = self.generate_count(count);
+                // Like `*` and `+`, a counted repetition scopes the cuts of its item.
+                self.scope_cut(contains_cut(&args[0]), || {
+                    let item = self.generate_parser_expr(&args[0]);
+                    quote_spanned! {span=>
+                        (|input: &mut I| -> ::winnow::ModalResult<Vec<_>, #error> {
+                            #count
+                            repeat(count, #item).parse_next(input)
+                        })
+                    }
+                })
+            }
+            Hook::BackRef(name) => {
+                let label = format!("same text as `{}`", name);
+                let matched = match self.ext.input {
+                    InputKind::Chars => quote_spanned! {span=>
+                        (ws, literal(AsRef::<str>::as_ref(&#name)))
+                            .map(|(_, s)| AsRef::<str>::as_ref(&s).to_string())
+                    },
+                    InputKind::Bytes => quote_spanned! {span=>
+                        (ws, literal(AsRef::<[u8]>::as_ref(&#name)))
+                            .map(|(_, s)| AsRef::<[u8]>::as_ref(&s).to_vec())
+                    },
+                };
+                quote_spanned! {span=>
+                    #matched
+                        .context(::winnow::error::StrContext::Expected(
+                            ::winnow::error::StrContextValue::Description(#label),
+                        ))
+                }
+            }
+            Hook::InRange(range) => {
+                let item = self.generate_parser_expr(&args[0]);
+                let label = format!("value in {}", compact(range));
+                quote_spanned! {span=>
+                    (|input: &mut I| -> ::winnow::ModalResult<_, #error> {
+                        // Skip whitespace first so the error points at the value itself.
+                        ws.parse_next(input)?;
+                        let start = ::winnow::stream::Stream::checkpoint(input);
+                        let value = #item.parse_next(input)?;
+                        if (#range).contains(&value) {
+                            Ok(value)
+                        } else {
+                            ::winnow::stream::Stream::reset(input, &start);
+                            Err(::winnow::error::ErrMode::Backtrack(
+                                ::winnow::error::AddContext::add_context(
+                                    <#error as ::winnow::error::ParserError<I>>::from_input(input),
+                                    input,
+                                    &start,
+                                    ::winnow::error::StrContext::Expected(
+                                        ::winnow::error::StrContextValue::Description(#label),
+                                    ),
+                                ),
+                            ))
+                        }
+                    })
+                }
+            }
+            Hook::Expect(label) => {
+                let item = self.generate_parser_expr(&args[0]);
+                quote_spanned! {span=>
+                    (|input: &mut I| -> ::winnow::ModalResult<_, #error> {
+                        ::winnow_grammar::error::expect(input, #label, ws, #item)
+                    })
+                }
+            }
+            Hook::TryRecover => {
+                let body = self.rollback(
+                    self.generate_parser_expr(&args[0]),
+                    may_touch_state(&args[0]),
+                );
+                let sync = self.rollback(
+                    self.generate_parser_expr(&args[1]),
+                    may_touch_state(&args[1]),
+                );
+                let literals = sync_literals(&args[1]);
+                let recovery = self.recovery(&args[1]);
+                quote_spanned! {span=>
+                    (|input: &mut I| -> ::winnow::ModalResult<_, #error> {
+                        ::winnow_grammar::recover::try_recover(input, #body, #sync, &[#(#literals),*], #recovery)
+                    })
+                }
+            }
+            Hook::RecoverNode(rule) => {
+                let body = self.rollback(self.generate_parser_expr(&args[0]), true);
+                let sync = self.rollback(
+                    self.generate_parser_expr(&args[1]),
+                    may_touch_state(&args[1]),
+                );
+                let literals = sync_literals(&args[1]);
+                let recovery = self.recovery(&args[1]);
+                let build = self
+                    .ext
+                    .on_error(rule)
+                    .expect("`recover_node` is checked to call a rule with `#[on_error]`");
+                quote_spanned! {span=>
+                    (|input: &mut I| -> ::winnow::ModalResult<_, #error> {
+                        let result = ::winnow_grammar::recover::try_recover(input, #body, #sync, &[#(#literals),*], #recovery)?;
+                        Ok(::winnow_grammar::recover::error_node(result, #build))
+                    })
+                }
+            }
+            Hook::Closer => quote_spanned! {span=> ::winnow::combinator::fail::<_, (), _> },
+            Hook::LeftCall => self.generate_parser_expr(&args[0]),
+            Hook::Precedence(operators, ty) => {
+                let atom = self.generate_parser_expr(&args[0]);
+                self.generate_precedence(operators, ty, atom)
+            }
+            Hook::IntLiteral(ty) => quote_spanned! {span=>
+                (ws, ::winnow_grammar::integer::int_literal::<_, #ty, _>)
+                    .map(|(_, n)| n)
+                    .context(::winnow::error::StrContext::Expected(
+                        ::winnow::error::StrContextValue::Description("integer literal"),
+                    ))
+            },
+            Hook::Bits(fields, kw_span) => {
+                if self.ext.input == InputKind::Chars {
+                    return quote_spanned! {*kw_span=>
+                        compile_error!("`bits { ... }` reads binary data and requires `input: bytes;` in the grammar")
+                    };
+                }
+                self.generate_bits_parser(fields)
+            }
+            _ => {
+                // Inside expressions (e.g. alternatives of a group) a statement
+                // hook runs as a standalone parser producing `()`.
+                let step = self.generate_hook_step(hook, false);
+                quote_spanned! {span=>
+                    (|input: &mut I| -> ::winnow::ModalResult<(), #error> {
+                        #step
+                        Ok(())
+                    })
+                }
+            }
+        }
+    }
+
+    /// A Pratt parser over `operators`: a prefix operator or the atom, then
+    /// postfix, infix and ternary operators folded into the left operand
+    /// while they bind at least as tightly as `min_bp`. The binding powers
+    /// are listed in `winnow_grammar::precedence`.
+    fn generate_precedence(
+        &self,
+        operators: &[Operator],
+        ty: &syn::Type,
+        atom: TokenStream,
+    ) -> TokenStream {
+        let span = Span::mixed_site();
+        let error = &self.error;
+        let op = |lit: &syn::Lit| {
+            self.generate_parser_expr(&ModelPattern::Lit {
+                binding: None,
+                lit: lit.clone(),
+            })
+        };
+
+        let prefix = operators
+            .iter()
+            .filter(|o| o.fixity == Fixity::Prefix)
+            .map(|o| {
+                let token = op(&o.literals[0]);
+                let operand = &o.operands[0];
+                let rbp = 2 * o.level + 1;
+                let action = self.generate_action(&o.action);
+                quote_spanned! {span=>
+                    if opt(#token).parse_next(input)?.is_some() {
+                        let #operand = expr(input, #rbp)?;
+                        #action
+                    } else
+                }
+            });
+
+        let has_nonassoc = operators.iter().any(|o| o.fixity == Fixity::NonAssoc);
+        let arms: Vec<TokenStream> = operators
+            .iter()
+            .filter(|o| o.fixity != Fixity::Prefix)
+            .map(|o| {
+                let token = op(&o.literals[0]);
+                let level = o.level;
+                let (lbp, rbp) = match o.fixity {
+                    Fixity::Right | Fixity::Ternary => (2 * level + 1, 2 * level),
+                    _ => (2 * level, 2 * level + 1),
+                };
+                let action = self.generate_action(&o.action);
+                let operands = &o.operands;
+                let fold = match o.fixity {
+                    Fixity::Postfix => {
+                        let operand = &operands[0];
+                        quote_spanned! {span=>
+                            let #operand = lhs;
+                        }
+                    }
+                    Fixity::Ternary => {
+                        let (cond, then, otherwise) = (&operands[0], &operands[1], &operands[2]);
+                        let colon = op(&o.literals[1]);
+                        quote_spanned! {span=>
+                            let #cond = lhs;
+                            let #then = expr(input, 0)?;
+                            #colon.parse_next(input)?;
+                            let #otherwise = expr(input, #rbp)?;
+                        }
+                    }
+                    _ => {
+                        let (left, right) = (&operands[0], &operands[1]);
+                        quote_spanned! {span=>
+                            let #left = lhs;
+                            let #right = expr(input, #rbp)?;
+                        }
+                    }
+                };
+                // A non-associative operator does not chain with another at
+                // its level: `a == b == c` stops after `a == b`.
+                let chain = match (o.fixity, has_nonassoc) {
+                    (Fixity::NonAssoc, _) => quote_spanned! {span=>
+                        if chained == Some(#level) {
+                            ::winnow::stream::Stream::reset(input, &start);
+                            break;
+                        }
+                        chained = Some(#level);
+                    },
+                    (_, true) => quote_spanned! {span=> chained = None; },
+                    (_, false) => quote! {},
+                };
+                quote_spanned! {span=>
+                    if opt(#token).parse_next(input)?.is_some() {
+                        if #lbp < min_bp {
+                            ::winnow::stream::Stream::reset(input, &start);
+                            break;
+                        }
+                        #chain
+                        #fold
+                        lhs = #action;
+                        continue;
+                    }
+                }
+            })
+            .collect();
+
+        // Only bind what the operators use, so the expansion has no unused names.
+        let expr = if operators.iter().all(|o| o.fixity == Fixity::Postfix) {
+            quote_spanned! {span=> _ }
+        } else {
+            quote_spanned! {span=> expr }
+        };
+        let body = if arms.is_empty() {
+            quote_spanned! {span=>
+                let _ = min_bp;
+                Ok(#(#prefix)* { #atom.parse_next(input)? })
+            }
+        } else {
+            let chained = if has_nonassoc {
+                quote_spanned! {span=> let mut chained: Option<u32> = None; }
+            } else {
+                quote! {}
+            };
+            quote_spanned! {span=>
+                let mut lhs = #(#prefix)* { #atom.parse_next(input)? };
+                #chained
+                loop {
+                    let start = ::winnow::stream::Stream::checkpoint(input);
+                    #(#arms)*
+                    break;
+                }
+                Ok(lhs)
+            }
+        };
+
+        quote_spanned! {span=>
+            (|input: &mut I| -> ::winnow::ModalResult<#ty, #error> {
+                ::winnow_grammar::precedence::climb::<I, #ty, #error>(input, 0, &|input, min_bp, #expr| {
+                    #body
+                })
+            })
+        }
+    }
+
+    /// Reads the fields of a `bits` block and returns the named ones as a tuple.
+    fn generate_bits_parser(&self, fields: &[BitField]) -> TokenStream {
+        let span = Span::mixed_site();
+        let mut reads = Vec::new();
+        let mut values = Vec::new();
+        let mut types = Vec::new();
+
+        for (i, field) in fields.iter().enumerate() {
+            let width = field.width;
+            // The smallest unsigned integer holding the field.
+            let ty = match width {
+                1..=8 => quote_spanned! {span=> u8 },
+                9..=16 => quote_spanned! {span=> u16 },
+                17..=32 => quote_spanned! {span=> u32 },
+                33..=64 => quote_spanned! {span=> u64 },
+                _ => quote_spanned! {span=> u128 },
+            };
+            let value = format_ident!("field_{}", i, span = span);
+            let target = match field.name {
+                Some(_) => quote! { #value },
+                None => quote! { _ },
+            };
+            reads.push(quote_spanned! {span=>
+                let #target: #ty = ::winnow::binary::bits::take(#width).parse_next(bits)?;
+            });
+            if field.name.is_some() {
+                values.push(value);
+                types.push(ty);
+            }
+        }
+
+        // The bit-level parser always uses `ContextError`; its failures are
+        // re-raised as the grammar's error at the byte position.
+        let error = &self.error;
+        quote_spanned! {span=>
+            (|input: &mut I| -> ::winnow::ModalResult<(#(#types,)*), #error> {
+                ::winnow::binary::bits::bits::<_, _, ::winnow::error::ContextError, ::winnow::error::ContextError, _>(
+                    |bits: &mut (I, usize)| -> ::winnow::ModalResult<(#(#types,)*)> {
+                        #(#reads)*
+                        Ok((#(#values,)*))
+                    },
+                )
+                .parse_next(input)
+                .map_err(|e| e.map(|_| ::winnow::error::ParserError::from_input(input)))
+            })
+        }
+    }
+
+    /// Evaluates a user count expression into `count: usize`, backtracking if it does not fit.
+    fn generate_count(&self, expr: &TokenStream) -> TokenStream {
+        let span = Span::mixed_site();
+        quote_spanned! {span=>
+            let count: usize = match ::std::convert::TryFrom::try_from(#expr) {
+                Ok(count) => count,
+                Err(_) => {
+                    return Err(::winnow::error::ErrMode::Backtrack(
+                        ::winnow::error::ParserError::from_input(input),
+                    ))
+                }
+            };
+        }
+    }
+
     fn generate_delimited_step(
         &self,
         inner: &[ModelPattern],
@@ -415,9 +1093,12 @@ impl<'a> Codegen<'a> {
         let span = Span::mixed_site();
 
         // Open delimiter
-        let open_parser = quote_spanned! {span=> (ws, literal(#open)) };
-        let open_stmt = if in_cut {
-            quote_spanned! {span=> let _ = ::winnow::combinator::cut_err(#open_parser).parse_next(input)?; }
+        let open_parser = quote_spanned! {span=> (ws, literal(#open).context(::winnow::error::StrContext::Expected(::winnow::error::StrContextValue::StringLiteral(#open)))) };
+        let open_stmt = if in_cut && self.ext.repair.is_some() {
+            quote_spanned! {span=> ::winnow_grammar::recover::repair(input, #open, #open_parser)?; }
+        } else if in_cut {
+            let open_parser = self.cut(open_parser);
+            quote_spanned! {span=> let _ = #open_parser.parse_next(input)?; }
         } else {
             quote_spanned! {span=> let _ = #open_parser.parse_next(input)?; }
         };
@@ -428,13 +1109,15 @@ impl<'a> Codegen<'a> {
         let inner_steps = self.generate_sequence_steps(inner, in_cut);
 
         // Check if inner triggers cut
-        let inner_triggers_cut = inner.iter().any(|p| matches!(p, ModelPattern::Cut(_)));
-        let final_cut = in_cut || inner_triggers_cut;
+        let final_cut = in_cut || inner.iter().any(contains_cut);
 
         // Close delimiter
-        let close_parser = quote_spanned! {span=> (ws, literal(#close)) };
-        let close_stmt = if final_cut {
-            quote_spanned! {span=> let _ = ::winnow::combinator::cut_err(#close_parser).parse_next(input)?; }
+        let close_parser = quote_spanned! {span=> (ws, literal(#close).context(::winnow::error::StrContext::Expected(::winnow::error::StrContextValue::StringLiteral(#close)))) };
+        let close_stmt = if final_cut && self.ext.repair.is_some() {
+            quote_spanned! {span=> ::winnow_grammar::recover::repair(input, #close, #close_parser)?; }
+        } else if final_cut {
+            let close_parser = self.cut(close_parser);
+            quote_spanned! {span=> let _ = #close_parser.parse_next(input)?; }
         } else {
             quote_spanned! {span=> let _ = #close_parser.parse_next(input)?; }
         };
@@ -453,6 +1136,11 @@ impl<'a> Codegen<'a> {
                 syn::Lit::Int(_) | syn::Lit::Bool(_) => quote_spanned! {span=> #lit },
                 _ => self.generate_parser_expr(pattern),
             },
+            ModelPattern::RuleCall {
+                rule_name, args, ..
+            } if args.is_empty() && self.value_params.borrow().contains(rule_name) => {
+                quote! { #rule_name }
+            }
             _ => self.generate_parser_expr(pattern),
         }
     }
@@ -465,6 +1153,10 @@ impl<'a> Codegen<'a> {
         let span = Span::mixed_site();
         let name_str = rule_name.to_string();
 
+        if let Some(hook) = self.ext.hook(rule_name) {
+            return self.generate_hook_parser(hook, args);
+        }
+
         if self.user_rules.contains(&name_str) {
             let fn_name = format_ident!("parse_{}", rule_name, span = span);
             if args.is_empty() {
@@ -475,6 +1167,42 @@ impl<'a> Codegen<'a> {
             }
         }
 
+        if let Some(msg) = self.check_builtin_input(&name_str) {
+            return quote_spanned! {rule_name.span()=> compile_error!(#msg) };
+        }
+
+        let parser = self.generate_builtin_parser(rule_name, args);
+        match builtin_expectation(&name_str) {
+            Some(description) => quote_spanned! {span=>
+                #parser.context(::winnow::error::StrContext::Expected(
+                    ::winnow::error::StrContextValue::Description(#description),
+                ))
+            },
+            None => parser,
+        }
+    }
+
+    fn generate_builtin_parser(
+        &self,
+        rule_name: &syn::Ident,
+        args: &[ModelPattern],
+    ) -> TokenStream {
+        let span = Span::mixed_site();
+        let name_str = rule_name.to_string();
+
+        if let Some(parser) = float_builtin(&name_str) {
+            return quote_spanned! {span=> (ws, #parser).map(|(_, f)| f) };
+        }
+
+        if let Some(parser) = radix_builtin(&name_str) {
+            return quote_spanned! {span=> (ws, #parser).map(|(_, n)| n) };
+        }
+
+        if let Some(parser) = binary_builtin(&name_str, &self.error) {
+            // Binary fields are read as-is: no whitespace is skipped before them.
+            return parser;
+        }
+
         match name_str.as_str() {
             "ident" => quote_spanned! {span=>
                 (ws, ::winnow::token::take_while(1.., |c| ::winnow::stream::AsChar::as_char(c).is_alphanumeric() || ::winnow::stream::AsChar::as_char(c) == '_'))
@@ -629,8 +1357,34 @@ impl<'a> Codegen<'a> {
         }
     }
 
+    /// Rejects builtins that do not fit the grammar's input kind.
+    fn check_builtin_input(&self, name: &str) -> Option<String> {
+        match self.ext.input {
+            InputKind::Chars if binary_builtin(name, &self.error).is_some() => Some(format!(
+                "`{}` reads binary data and requires `input: bytes;` in the grammar",
+                name
+            )),
+            InputKind::Bytes if name == "any" => Some(
+                "`any` reads a character; use `be_u8` to read a single byte in a grammar with `input: bytes;`"
+                    .to_string(),
+            ),
+            InputKind::Bytes
+                if TEXT_BUILTINS.contains(&name)
+                    || float_builtin(name).is_some()
+                    || radix_builtin(name).is_some() =>
+            {
+                Some(format!(
+                    "`{}` reads text and cannot be used in a grammar with `input: bytes;`",
+                    name
+                ))
+            }
+            _ => None,
+        }
+    }
+
     fn generate_parser_expr(&self, pattern: &ModelPattern) -> TokenStream {
         let span = Span::mixed_site();
+        let error = &self.error;
         match pattern {
             ModelPattern::SpanBinding(inner, _, _) => {
                 let p = self.generate_parser_expr(inner);
@@ -640,70 +1394,45 @@ impl<'a> Codegen<'a> {
                 rule_name, args, ..
             } => self.generate_rule_call_parser(rule_name, args),
             ModelPattern::Lit { lit, .. } => {
+                let expected = expected_literal(lit);
                 quote_spanned! {span=>
-                    (ws, literal(#lit)).map(|(_, s)| s)
+                    (ws, literal(#lit)).map(|(_, s)| s)#expected
                 }
             }
+            // Only a choice scopes its cuts: the cut of a single sequence
+            // belongs to the enclosing one, as `contains_cut` has it.
             ModelPattern::Group(alternatives, _) => {
-                let alts: Vec<TokenStream> = alternatives
-                    .iter()
-                    .map(|seq| self.generate_sequence_parser(seq))
-                    .collect();
-                quote_spanned! {span=>
-                    alt(( #(#alts),* ))
-                }
+                let scoped = alternatives.len() > 1
+                    && alternatives.iter().any(|seq| seq.iter().any(contains_cut));
+                self.scope_cut(scoped, || self.generate_group(alternatives))
             }
-            ModelPattern::Optional(inner, _) => {
-                let p = self.generate_parser_expr(inner);
+            ModelPattern::Optional(inner, _) => self.scope_cut(contains_cut(inner), || {
+                let p = self.rollback(self.generate_parser_expr(inner), may_touch_state(inner));
                 quote_spanned! {span=> opt(#p) }
-            }
-            ModelPattern::Repeat(inner, _span) => {
-                let p = self.generate_parser_expr(inner);
+            }),
+            ModelPattern::Repeat(inner, _span) => self.scope_cut(contains_cut(inner), || {
+                let p = self.rollback(self.generate_parser_expr(inner), may_touch_state(inner));
                 quote_spanned! {span=> repeat(0.., #p) }
-            }
-            ModelPattern::Plus(inner, _span) => {
-                let p = self.generate_parser_expr(inner);
+            }),
+            ModelPattern::Plus(inner, _span) => self.scope_cut(contains_cut(inner), || {
+                let p = self.rollback(self.generate_parser_expr(inner), may_touch_state(inner));
                 quote_spanned! {span=> repeat(1.., #p) }
-            }
+            }),
             ModelPattern::Parenthesized(inner, _) => self.generate_delimited_expr(inner, "(", ")"),
             ModelPattern::Bracketed(inner, _) => self.generate_delimited_expr(inner, "[", "]"),
             ModelPattern::Braced(inner, _) => self.generate_delimited_expr(inner, "{", "}"),
             ModelPattern::Cut(_) => quote_spanned! {span=> ::winnow::combinator::empty }, // Should be handled by sequence logic, but fallback to empty
             ModelPattern::Recover { body, sync, .. } => {
-                let body_parser = self.generate_parser_expr(body);
-                let sync_parser = self.generate_parser_expr(sync);
-                // recover(rule, sync_token) logic:
-                // 1. Try `rule`
-                // 2. If it fails, eat tokens until `sync_token` matches (peek).
-                // 3. If synced, return None (or default for Option).
-                //
-                // Note: The syn-grammar recover pattern assumes the return type is Option<T>
-                // or that we are wrapping it in Option.
-                // However, `winnow` doesn't have a direct `recover` combinator that behaves exactly like syn-grammar's spec (skip until).
-                // We can simulate it with `alt` + `repeat_until`.
-
-                // Logic:
-                // alt((
-                //   body_parser.map(Some),
-                //   (
-                //      repeat(0.., (not(sync_parser), any)).map(|()| ()), // Skip until sync
-                //      // We assume the sync token is NOT consumed by recover itself, but checked?
-                //      // "Note that recover does not consume the sync token." - from README
-                //      ::winnow::combinator::peek(sync_parser)
-                //   ).map(|_| None)
-                // ))
-
+                let body_parser =
+                    self.rollback(self.generate_parser_expr(body), may_touch_state(body));
+                let sync_parser =
+                    self.rollback(self.generate_parser_expr(sync), may_touch_state(sync));
+                let literals = sync_literals(sync);
+                let recovery = self.recovery(sync);
                 quote_spanned! {span=>
-                    alt((
-                        #body_parser.map(Some),
-                        (
-                            ::winnow::combinator::repeat(0.., (
-                                ::winnow::combinator::not(::winnow::combinator::peek(#sync_parser)),
-                                ::winnow::token::any
-                            )).map(|()| ()),
-                            #sync_parser
-                        ).map(|_| None)
-                    ))
+                    (|input: &mut I| -> ::winnow::ModalResult<_, #error> {
+                        ::winnow_grammar::recover::recover(input, #body_parser, #sync_parser, &[#(#literals),*], #recovery)
+                    })
                 }
             }
             ModelPattern::Peek(inner, _) => {
@@ -717,6 +1446,67 @@ impl<'a> Codegen<'a> {
         }
     }
 
+    fn generate_group(&self, alternatives: &[Vec<ModelPattern>]) -> TokenStream {
+        let span = Span::mixed_site();
+        let error = &self.error;
+        let alts: Vec<TokenStream> = alternatives
+            .iter()
+            .map(|seq| {
+                let parser = self.generate_sequence_parser(seq);
+                if alternatives.len() == 1 {
+                    return parser;
+                }
+                self.rollback(parser, seq.iter().any(may_touch_state))
+            })
+            .collect();
+        // `alt((x))` would treat the tuple parser `x` as a list of alternatives.
+        if alts.len() == 1 {
+            return alts.into_iter().next().unwrap();
+        }
+        let attempts = alts.iter().map(|alt| {
+            quote_spanned! {span=> ::winnow::Parser::parse_next(&mut #alt, input) }
+        });
+        let keywords = alternatives.iter().filter_map(|seq| leading_keyword(seq));
+        let body = self.generate_alternatives(attempts, keywords);
+        quote_spanned! {span=>
+            (|input: &mut I| -> ::winnow::ModalResult<_, #error> { #body })
+        }
+    }
+
+    /// Makes a choice whose alternatives or iterations contain a cut fail with
+    /// a backtrack, so the cut commits only to that choice. A rule's own
+    /// alternatives are not scoped: their cut errors reach the caller.
+    fn scope_cut(&self, scoped: bool, generate: impl FnOnce() -> TokenStream) -> TokenStream {
+        if !scoped {
+            return generate();
+        }
+        let span = Span::mixed_site();
+        let error = &self.error;
+        let outer = self.cut_scope.replace(true);
+        let parser = generate();
+        self.cut_scope.set(outer);
+        quote_spanned! {span=>
+            (|input: &mut I| -> ::winnow::ModalResult<_, #error> {
+                let cut_scope = ::std::cell::Cell::new(false);
+                let mut parser = ::winnow_grammar::error::uncut(&cut_scope, #parser);
+                let result = ::winnow::Parser::parse_next(&mut parser, input);
+                result
+            })
+        }
+    }
+
+    /// Commits to `parser`: its failure is a cut error. Inside a scoped
+    /// choice the cut is recorded so that only its own cut errors are
+    /// turned back into backtracks.
+    fn cut(&self, parser: TokenStream) -> TokenStream {
+        let span = Span::mixed_site();
+        if self.cut_scope.get() {
+            quote_spanned! {span=> ::winnow_grammar::error::commit(&cut_scope, #parser) }
+        } else {
+            quote_spanned! {span=> ::winnow::combinator::cut_err(#parser) }
+        }
+    }
+
     fn generate_sequence_parser(&self, seq: &[ModelPattern]) -> TokenStream {
         let span = Span::mixed_site();
         let mut parsers = Vec::new();
@@ -730,10 +1520,11 @@ impl<'a> Codegen<'a> {
 
             let p_expr = self.generate_parser_expr(p);
             if in_cut {
-                parsers.push(quote_spanned! {span=> ::winnow::combinator::cut_err(#p_expr) });
+                parsers.push(self.cut(p_expr));
             } else {
                 parsers.push(p_expr);
             }
+            in_cut |= contains_cut(p);
         }
 
         if parsers.len() == 1 {
@@ -743,6 +1534,35 @@ impl<'a> Codegen<'a> {
         }
     }
 
+    /// Wraps a parser that the caller may backtrack out of, so that changes
+    /// it made to the user state are undone along with the input. Only
+    /// patterns that can reach an action need it.
+    fn rollback(&self, parser: TokenStream, touches_state: bool) -> TokenStream {
+        let span = Span::mixed_site();
+        if self.ext.state.is_some() && touches_state {
+            quote_spanned! {span=> ::winnow_grammar::state::rollback(#parser) }
+        } else {
+            parser
+        }
+    }
+
+    /// The `winnow_grammar::recover::Recovery` mode for a recovery up to
+    /// `sync`: the grammar's, or balanced when there is no sync token and
+    /// recovery ends at a closer.
+    fn recovery(&self, sync: &ModelPattern) -> TokenStream {
+        let span = Span::mixed_site();
+        let closer = matches!(sync, ModelPattern::RuleCall { rule_name, .. }
+            if matches!(self.ext.hook(rule_name), Some(Hook::Closer)));
+        match self.ext.recovery {
+            RecoveryKind::Flat if !closer => {
+                quote_spanned! {span=> ::winnow_grammar::recover::Recovery::Flat }
+            }
+            _ => {
+                quote_spanned! {span=> ::winnow_grammar::recover::Recovery::Balanced }
+            }
+        }
+    }
+
     fn generate_delimited_expr(
         &self,
         inner: &[ModelPattern],
@@ -751,11 +1571,325 @@ impl<'a> Codegen<'a> {
     ) -> TokenStream {
         let span = Span::mixed_site();
         let inner_parser = self.generate_sequence_parser(inner);
+        let close_parser = quote_spanned! {span=>
+            (ws, literal(#close).context(::winnow::error::StrContext::Expected(::winnow::error::StrContextValue::StringLiteral(#close))))
+        };
+        // A cut inside the delimiters also commits to the closer.
+        let close_parser = if inner.iter().any(contains_cut) {
+            self.cut(close_parser)
+        } else {
+            close_parser
+        };
+
+        quote_spanned! {span=>
+            delimited(
+                (ws, literal(#open).context(::winnow::error::StrContext::Expected(::winnow::error::StrContextValue::StringLiteral(#open)))),
+                #inner_parser,
+                #close_parser,
+            )
+        }
+    }
 
+    /// Tries each alternative in turn, returning the first success or cut. If
+    /// all of them backtrack, the error is the one that got furthest, with the
+    /// expectations of ties merged. Each attempt is an expression over `input`.
+    /// `keywords` are the words alternatives start with, offered as "did you
+    /// mean" suggestions for a misspelled first word.
+    fn generate_alternatives(
+        &self,
+        attempts: impl Iterator<Item = TokenStream>,
+        keywords: impl Iterator<Item = String>,
+    ) -> TokenStream {
+        let span = Span::mixed_site();
+        let error = &self.error;
+        let mut keywords: Vec<String> = keywords.collect();
+        keywords.sort();
+        keywords.dedup();
+        // Suggestions are attached as the cause of a `ContextError`.
+        let finish = if self.ext.error.is_none()
+            && self.ext.input == InputKind::Chars
+            && !keywords.is_empty()
+        {
+            quote_spanned! {span=>
+                let error = furthest.finish(input);
+                Err(::winnow_grammar::error::suggest(input, &start, error, &[#(#keywords),*]))
+            }
+        } else {
+            quote_spanned! {span=> Err(furthest.finish(input)) }
+        };
+        // Only `ContextError` exposes its expectations for merging.
+        let record = if self.ext.error.is_none() {
+            format_ident!("record_merged", span = span)
+        } else {
+            format_ident!("record", span = span)
+        };
         quote_spanned! {span=>
-            delimited((ws, literal(#open)), #inner_parser, (ws, literal(#close)))
+            'alternatives: {
+                let start = ::winnow::stream::Stream::checkpoint(input);
+                let mut furthest = ::winnow_grammar::error::Furthest::<_, #error>::new();
+                #(
+                    match #attempts {
+                        Err(::winnow::error::ErrMode::Backtrack(e)) => {
+                            furthest.#record(input, &start, e);
+                            ::winnow::stream::Stream::reset(input, &start);
+                        }
+                        result => break 'alternatives result,
+                    }
+                )*
+                #finish
+            }
+        }
+    }
+}
+
+/// The word an alternative starts with, if its first pattern is a keyword
+/// literal such as `"select"`.
+fn leading_keyword(seq: &[ModelPattern]) -> Option<String> {
+    let Some(ModelPattern::Lit {
+        lit: syn::Lit::Str(lit),
+        ..
+    }) = seq.first()
+    else {
+        return None;
+    };
+    let word = lit.value();
+    let is_word = word.starts_with(|c: char| c.is_alphabetic() || c == '_')
+        && word.chars().all(|c| c.is_alphanumeric() || c == '_');
+    is_word.then_some(word)
+}
+
+/// Whether the arguments of a recursive call are the rule's parameters, in order.
+fn forwards_params(args: &[ModelPattern], params: &[(syn::Ident, Option<syn::Type>)]) -> bool {
+    args.len() == params.len()
+        && args.iter().zip(params).all(|(arg, (param, _))| {
+            matches!(arg, ModelPattern::RuleCall { rule_name, args, binding: None }
+                if rule_name == param && args.is_empty())
+        })
+}
+
+/// Whether a cut in `pattern` applies to the sequence containing it: the
+/// pattern is a cut, or a group of one sequence or a delimited pattern with
+/// such a cut inside.
+fn contains_cut(pattern: &ModelPattern) -> bool {
+    match pattern {
+        ModelPattern::Cut(_) => true,
+        ModelPattern::Group(alternatives, _) if alternatives.len() == 1 => {
+            alternatives[0].iter().any(contains_cut)
+        }
+        ModelPattern::Parenthesized(inner, _)
+        | ModelPattern::Bracketed(inner, _)
+        | ModelPattern::Braced(inner, _) => inner.iter().any(contains_cut),
+        _ => false,
+    }
+}
+
+/// Whether `pattern` can run an action, and with it change the user state:
+/// anything but literals and cuts may call a rule.
+fn may_touch_state(pattern: &ModelPattern) -> bool {
+    match pattern {
+        ModelPattern::Lit { .. } | ModelPattern::Cut(_) => false,
+        ModelPattern::Group(alternatives, _) => alternatives.iter().flatten().any(may_touch_state),
+        ModelPattern::Parenthesized(inner, _)
+        | ModelPattern::Bracketed(inner, _)
+        | ModelPattern::Braced(inner, _) => inner.iter().any(may_touch_state),
+        ModelPattern::Optional(inner, _)
+        | ModelPattern::Repeat(inner, _)
+        | ModelPattern::Plus(inner, _)
+        | ModelPattern::SpanBinding(inner, _, _)
+        | ModelPattern::Peek(inner, _)
+        | ModelPattern::Not(inner, _) => may_touch_state(inner),
+        ModelPattern::Recover { body, sync, .. } => may_touch_state(body) || may_touch_state(sync),
+        _ => true,
+    }
+}
+
+/// The text matched by a string or character literal pattern.
+fn literal_text(pattern: &ModelPattern) -> Option<String> {
+    match pattern {
+        ModelPattern::Lit {
+            lit: syn::Lit::Str(s),
+            ..
+        } => Some(s.value()),
+        ModelPattern::Lit {
+            lit: syn::Lit::Char(c),
+            ..
+        } => Some(c.value().to_string()),
+        _ => None,
+    }
+}
+
+/// The literals a recovery sync pattern can start with, if it is a literal
+/// or a choice of single literals. Empty if it is anything else.
+fn sync_literals(sync: &ModelPattern) -> Vec<String> {
+    let literals = match sync {
+        ModelPattern::Group(alternatives, _) => alternatives
+            .iter()
+            .map(|seq| match &seq[..] {
+                [only] => literal_text(only),
+                _ => None,
+            })
+            .collect(),
+        pattern => literal_text(pattern).map(|l| vec![l]),
+    };
+    // An empty literal matches everywhere, so there is nothing to scan for.
+    literals
+        .filter(|ls: &Vec<String>| ls.iter().all(|l| !l.is_empty()))
+        .unwrap_or_default()
+}
+
+/// The `.context(...)` naming a literal in errors.
+fn expected_literal(lit: &syn::Lit) -> TokenStream {
+    let span = Span::mixed_site();
+    let value = match lit {
+        syn::Lit::Str(s) => {
+            quote_spanned! {span=> ::winnow::error::StrContextValue::StringLiteral(#s) }
+        }
+        syn::Lit::Char(c) => {
+            quote_spanned! {span=> ::winnow::error::StrContextValue::CharLiteral(#c) }
+        }
+        _ => return quote! {},
+    };
+    quote_spanned! {span=> .context(::winnow::error::StrContext::Expected(#value)) }
+}
+
+/// What a builtin is called in "expected ..." messages.
+fn builtin_expectation(name: &str) -> Option<&'static str> {
+    let description = match name {
+        "ident" => "identifier",
+        "string" => "string literal",
+        "char" => "character literal",
+        "alpha1" => "letter",
+        "digit1" => "digit",
+        "hex_digit1" => "hexadecimal digit",
+        "oct_digit1" => "octal digit",
+        "binary_digit1" => "binary digit",
+        "space1" | "multispace1" => "whitespace",
+        "line_ending" => "line ending",
+        "eof" => "end of input",
+        "bool" => "`true` or `false`",
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128"
+        | "isize" => "integer",
+        "f32" | "f64" => "number",
+        _ => match name.split_once('_') {
+            Some(("hex", "f32" | "f64")) => "hexadecimal float",
+            Some(("hex", _)) => "hexadecimal integer",
+            Some(("oct", _)) => "octal integer",
+            Some(("bin", _)) => "binary integer",
+            Some(("json" | "toml" | "rust", _)) => "number",
+            _ => return None,
+        },
+    };
+    Some(description)
+}
+
+/// Renders tokens without the spaces `TokenStream::to_string` puts between them.
+fn compact(tokens: &TokenStream) -> String {
+    tokens.clone().into_iter().map(|t| t.to_string()).collect()
+}
+
+/// Builtins that read text, such as `String`s or decimal numbers, and
+/// therefore need `char` input.
+const TEXT_BUILTINS: &[&str] = &[
+    "ident",
+    "string",
+    "char",
+    "alpha1",
+    "digit1",
+    "hex_digit0",
+    "hex_digit1",
+    "oct_digit0",
+    "oct_digit1",
+    "binary_digit0",
+    "binary_digit1",
+    "space0",
+    "space1",
+    "multispace0",
+    "multispace1",
+    "line_ending",
+    "bool",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "usize",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+    "isize",
+    "f32",
+    "f64",
+];
+
+/// Maps the float dialects (`json_f64`, `toml_f32`, ...) and `json_decimal`
+/// onto `winnow_grammar::float`.
+fn float_builtin(name: &str) -> Option<TokenStream> {
+    let span = Span::mixed_site();
+    if name == "json_decimal" {
+        return Some(quote_spanned! {span=> ::winnow_grammar::float::decimal::<_, _> });
+    }
+    let (dialect, ty) = name.split_once('_')?;
+    let supported =
+        matches!(dialect, "json" | "toml" | "rust" | "hex") && matches!(ty, "f32" | "f64");
+    if !supported {
+        return None;
+    }
+    let parser = format_ident!("{}", dialect, span = span);
+    let ty = format_ident!("{}", ty, span = span);
+    Some(quote_spanned! {span=> ::winnow_grammar::float::#parser::<_, #ty, _> })
+}
+
+/// Maps `hex_u32`, `oct_u8`, `bin_u16` and friends onto `winnow_grammar::integer`.
+fn radix_builtin(name: &str) -> Option<TokenStream> {
+    let span = Span::mixed_site();
+    let (radix, ty) = name.split_once('_')?;
+    let supported = matches!(radix, "hex" | "oct" | "bin")
+        && matches!(ty, "u8" | "u16" | "u32" | "u64" | "u128" | "usize");
+    if !supported {
+        return None;
+    }
+    let parser = format_ident!("{}", radix, span = span);
+    let ty = format_ident!("{}", ty, span = span);
+    Some(quote_spanned! {span=> ::winnow_grammar::integer::#parser::<_, #ty, _> })
+}
+
+/// Maps the byte-stream builtins onto `winnow::binary` and the LEB128
+/// helpers in `winnow_grammar::binary`.
+fn binary_builtin(name: &str, error: &TokenStream) -> Option<TokenStream> {
+    let span = Span::mixed_site();
+    let (endian, ty) = match name {
+        "uleb128" => {
+            return Some(quote_spanned! {span=> ::winnow_grammar::binary::uleb128::<_, #error> })
+        }
+        "sleb128" => {
+            return Some(quote_spanned! {span=> ::winnow_grammar::binary::sleb128::<_, #error> })
         }
+        _ => name.split_once('_')?,
+    };
+    let supported = matches!(endian, "be" | "le")
+        && matches!(
+            ty,
+            "u8" | "i8"
+                | "u16"
+                | "u24"
+                | "u32"
+                | "u64"
+                | "u128"
+                | "i16"
+                | "i24"
+                | "i32"
+                | "i64"
+                | "i128"
+                | "f32"
+                | "f64"
+        );
+    if !supported {
+        return None;
     }
+    let parser = format_ident!("{}", name, span = span);
+    Some(quote_spanned! {span=> ::winnow::binary::#parser::<_, #error> })
 }
 
 fn get_inner_binding(pattern: &ModelPattern) -> Option<&syn::Ident> {
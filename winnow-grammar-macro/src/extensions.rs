@@ -0,0 +1,1217 @@
+//! Grammar syntax handled by this backend rather than by `syn-grammar-model`.
+//!
+//! The upstream parser only understands the core EBNF forms. Anything beyond
+//! that is lowered here, before `parse_grammar` runs: each extension is
+//! replaced by a call to a synthetic *hook* rule (`__wg_hook_N`), and the
+//! original tokens are recorded in [`Extensions`]. The codegen recognises
+//! these calls and expands the recorded hook in place.
+//!
+//! A trivial definition is appended for every hook so that the upstream
+//! validator sees an ordinary, defined rule.
+
+use proc_macro2::{Delimiter, Group, Span, TokenStream, TokenTree};
+use quote::{format_ident, quote};
+use std::ops::Range;
+
+const HOOK_PREFIX: &str = "__wg_hook_";
+
+/// An extension recorded during lowering, expanded again by the codegen.
+pub enum Hook {
+    /// `&{ cond }` inside a pattern, or a trailing `if cond` guard on an
+    /// alternative. Backtracks unless `cond` evaluates to `true`.
+    Predicate(TokenStream),
+    /// `=name`: matches exactly the text bound to `name` earlier in the sequence.
+    BackRef(syn::Ident),
+    /// `take(n)`: the next `n` tokens as a slice.
+    Take(TokenStream),
+    /// `pattern{n}`: exactly `n` repetitions of the hook's single argument.
+    Count(TokenStream),
+    /// `pattern in lo..=hi`: the hook's single argument, rejected unless its
+    /// value lies in the range.
+    InRange(TokenStream),
+    /// `int_literal::<T>`: a Rust-style integer literal of type `T`.
+    IntLiteral(syn::Type),
+    /// `bits { version:u4 ihl:u4 ... }`: fixed-width bit fields, most significant bit first.
+    Bits(Vec<BitField>, Span),
+    /// `#[expect("description")] pattern`: the hook's single argument, with a
+    /// failure at its first token reported as expecting the description.
+    Expect(String),
+    /// `try_recover(rule, sync)`: the first argument, or on failure the error
+    /// and the input skipped up to the second argument.
+    TryRecover,
+    /// `recover_node(rule, sync)`: the first argument, a call of `rule`, or on
+    /// failure the value its `#[on_error(...)]` builds for the skipped input.
+    RecoverNode(syn::Ident),
+    /// The sync token of `recover(rule)` and `try_recover(rule)`, written
+    /// without one. Never matches, so recovery skips balanced delimiters up
+    /// to the closer of the enclosing ones.
+    Closer,
+    /// `precedence(atom) { operators }`: the hook's single argument as the
+    /// atom, combined by a Pratt loop over the declared operators. Holds the
+    /// rule's return type, which is the type of every operand.
+    Precedence(Vec<Operator>, syn::Type),
+    /// A leading call that closes a cycle of rules calling each other in
+    /// leading position, `a -> b -> a`. The single argument is the call. It
+    /// is wrapped so that the upstream validator accepts the grammar, and the
+    /// codegen unwraps it before resolving the recursion.
+    LeftCall,
+}
+
+/// One field of a `bits { ... }` block. Unnamed fields (`_:u3` or `u3`) are skipped.
+pub struct BitField {
+    pub name: Option<syn::Ident>,
+    pub width: usize,
+}
+
+/// One operator of a `precedence` rule, such as `left 1: l "+" r -> { l + r }`.
+pub struct Operator {
+    pub fixity: Fixity,
+    /// Higher levels bind more tightly.
+    pub level: u32,
+    /// The operator token, and the `:` of a ternary operator.
+    pub literals: Vec<syn::Lit>,
+    /// The names bound to the operands, in source order.
+    pub operands: Vec<syn::Ident>,
+    pub action: TokenStream,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Fixity {
+    Prefix,
+    Postfix,
+    Left,
+    Right,
+    NonAssoc,
+    Ternary,
+}
+
+impl Hook {
+    /// Number of patterns passed to the synthetic rule.
+    fn arity(&self) -> usize {
+        match self {
+            Hook::Count(_)
+            | Hook::InRange(_)
+            | Hook::Expect(_)
+            | Hook::Precedence(..)
+            | Hook::LeftCall => 1,
+            Hook::TryRecover | Hook::RecoverNode(_) => 2,
+            _ => 0,
+        }
+    }
+}
+
+/// The token type of the input stream, selected with `input: chars;` or `input: bytes;`.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputKind {
+    #[default]
+    Chars,
+    Bytes,
+}
+
+/// How `recover` skips input, selected with `recovery: flat;` or `recovery: balanced;`.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum RecoveryKind {
+    #[default]
+    Flat,
+    Balanced,
+}
+
+#[derive(Default)]
+pub struct Extensions {
+    /// The user state type from a `state: Type;` declaration.
+    pub state: Option<syn::Type>,
+    /// The error type from an `error: Type;` declaration, `ContextError` if absent.
+    pub error: Option<syn::Type>,
+    pub input: InputKind,
+    pub recovery: RecoveryKind,
+    /// Where `repair: true;` was declared, if it was.
+    pub repair: Option<Span>,
+    hooks: Vec<Hook>,
+    options_seen: Vec<String>,
+    /// Rule descriptions from `#[expect("...")]` or doc comments.
+    labels: Vec<(String, String)>,
+    /// Error value builders from `#[on_error(...)]`, with the attribute's span.
+    error_nodes: Vec<(String, syn::Expr, Span)>,
+    /// The names of the grammar's rules. A rule named like a built-in form,
+    /// such as `take`, is called instead of the form.
+    rules: Vec<String>,
+}
+
+impl Extensions {
+    /// Returns the hook behind a synthetic rule name, if it is one.
+    pub fn hook(&self, name: &syn::Ident) -> Option<&Hook> {
+        let index: usize = name.to_string().strip_prefix(HOOK_PREFIX)?.parse().ok()?;
+        self.hooks.get(index)
+    }
+
+    /// The description of a rule, used in its errors instead of its name.
+    pub fn label(&self, rule: &syn::Ident) -> Option<&str> {
+        self.labels
+            .iter()
+            .find(|(name, _)| rule == name)
+            .map(|(_, label)| label.as_str())
+    }
+
+    /// The `#[on_error(...)]` builder of a rule, called with the span and
+    /// text of the input skipped when the rule is recovered.
+    pub fn on_error(&self, rule: &syn::Ident) -> Option<&syn::Expr> {
+        self.error_nodes
+            .iter()
+            .find(|(name, ..)| rule == name)
+            .map(|(_, build, _)| build)
+    }
+
+    /// Applies a grammar-level `key: value;` option.
+    fn set_option(&mut self, key: &TokenTree, value: TokenStream) -> syn::Result<()> {
+        let name = key.to_string();
+        if self.options_seen.contains(&name) {
+            return Err(syn::Error::new(
+                key.span(),
+                format!("duplicate `{}` option", name),
+            ));
+        }
+        self.options_seen.push(name.clone());
+
+        match name.as_str() {
+            "state" => self.state = Some(syn::parse2(value)?),
+            "error" => self.error = Some(syn::parse2(value)?),
+            "repair" => {
+                let enabled: syn::LitBool = syn::parse2(value)?;
+                self.repair = enabled.value.then(|| key.span());
+            }
+            "recovery" => {
+                let kind: syn::Ident = syn::parse2(value)?;
+                self.recovery = match kind.to_string().as_str() {
+                    "flat" => RecoveryKind::Flat,
+                    "balanced" => RecoveryKind::Balanced,
+                    _ => {
+                        return Err(syn::Error::new(
+                            kind.span(),
+                            "expected `flat` or `balanced`",
+                        ))
+                    }
+                };
+            }
+            _ => {
+                let kind: syn::Ident = syn::parse2(value)?;
+                self.input = match kind.to_string().as_str() {
+                    "chars" => InputKind::Chars,
+                    "bytes" => InputKind::Bytes,
+                    _ => return Err(syn::Error::new(kind.span(), "expected `chars` or `bytes`")),
+                };
+            }
+        }
+        Ok(())
+    }
+
+    /// Records `hook` and emits the call to its synthetic rule into `out`.
+    fn push_hook(
+        &mut self,
+        hook: Hook,
+        span: Span,
+        out: &mut Vec<TokenTree>,
+        next: Option<&TokenTree>,
+    ) -> syn::Result<()> {
+        let name = format_ident!("{}{}", HOOK_PREFIX, self.hooks.len(), span = span);
+        self.hooks.push(hook);
+
+        // `name ( ... )` would be read as a call with arguments, and so would
+        // `ident (name)`. Wrap the call in a group only when it is followed by one.
+        if matches!(next, Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis) {
+            if matches!(out.last(), Some(TokenTree::Ident(_))) {
+                return Err(syn::Error::new(
+                    span,
+                    "ambiguous position for this pattern; wrap the preceding pattern in parentheses",
+                ));
+            }
+            out.push(TokenTree::Group(Group::new(
+                Delimiter::Parenthesis,
+                quote!(#name),
+            )));
+        } else {
+            out.push(TokenTree::Ident(name));
+        }
+        Ok(())
+    }
+
+    /// Completes the arguments of a `recover` written without a sync token
+    /// with [`Hook::Closer`].
+    fn default_sync(&mut self, args: &mut Vec<TokenTree>, span: Span) {
+        if args.is_empty() || args.iter().any(|t| is_punct(t, ',')) {
+            return;
+        }
+        let name = format_ident!("{}{}", HOOK_PREFIX, self.hooks.len(), span = span);
+        self.hooks.push(Hook::Closer);
+        args.push(TokenTree::Punct(proc_macro2::Punct::new(
+            ',',
+            proc_macro2::Spacing::Alone,
+        )));
+        args.push(TokenTree::Ident(name));
+    }
+
+    /// Records `hook` and replaces the pattern at the end of `out` with a
+    /// call to its synthetic rule, passing that pattern as the argument.
+    fn push_wrapping_hook(&mut self, hook: Hook, span: Span, out: &mut Vec<TokenTree>) {
+        let name = format_ident!("{}{}", HOOK_PREFIX, self.hooks.len(), span = span);
+        self.hooks.push(hook);
+
+        let atom_len = preceding_atom_len(out);
+        let atom: TokenStream = out.drain(out.len() - atom_len..).collect();
+        out.push(TokenTree::Ident(name));
+        out.push(TokenTree::Group(Group::new(Delimiter::Parenthesis, atom)));
+    }
+
+    fn hook_definitions(&self) -> TokenStream {
+        let defs = self.hooks.iter().enumerate().map(|(i, hook)| {
+            let name = format_ident!("{}{}", HOOK_PREFIX, i, span = Span::call_site());
+            let params = (0..hook.arity()).map(|p| format_ident!("p{}", p));
+            quote! { rule #name(#(#params),*) -> () = empty -> { () } }
+        });
+        quote! { #(#defs)* }
+    }
+}
+
+/// Lowers all extensions in a `grammar Name { ... }` invocation.
+pub fn lower(input: TokenStream) -> syn::Result<(TokenStream, Extensions)> {
+    let mut ext = Extensions::default();
+    let mut out = Vec::new();
+    let mut lowered = false;
+
+    for tt in input {
+        match tt {
+            TokenTree::Group(g) if g.delimiter() == Delimiter::Brace && !lowered => {
+                let mut body = ext.lower_grammar_body(g.stream())?;
+                if let (Some(span), None) = (ext.repair, &ext.state) {
+                    return Err(syn::Error::new(
+                        span,
+                        "`repair` needs a `state` type implementing `AsMut<winnow_grammar::recover::Repairs>`",
+                    ));
+                }
+                body.extend(ext.hook_definitions());
+                let mut group = Group::new(Delimiter::Brace, body);
+                group.set_span(g.span());
+                out.push(TokenTree::Group(group));
+                lowered = true;
+            }
+            other => out.push(other),
+        }
+    }
+
+    Ok((out.into_iter().collect(), ext))
+}
+
+impl Extensions {
+    fn lower_grammar_body(&mut self, body: TokenStream) -> syn::Result<TokenStream> {
+        let tokens: Vec<TokenTree> = body.into_iter().collect();
+        let mut out = Vec::new();
+        let mut i = 0;
+        // Attributes seen since the last item, applied to the next rule.
+        let mut expect: Option<(String, Span)> = None;
+        let mut on_error: Option<(syn::Expr, Span)> = None;
+        let mut doc: Vec<String> = Vec::new();
+        // Each rule's name and the range of its lowered body in `out`.
+        let mut bodies = Vec::new();
+        self.rules = tokens
+            .windows(2)
+            .filter(|pair| is_ident(&pair[0], "rule"))
+            .map(|pair| pair[1].to_string())
+            .collect();
+
+        while i < tokens.len() {
+            if let Some(attr) = attribute(&tokens, i) {
+                match RuleAttribute::parse(attr)? {
+                    Some(RuleAttribute::Expect(label)) => expect = Some((label, attr.span())),
+                    Some(RuleAttribute::OnError(build)) => on_error = Some((build, attr.span())),
+                    Some(RuleAttribute::Doc(line)) => doc.push(line),
+                    None => out.extend(tokens[i..i + 2].iter().cloned()),
+                }
+                i += 2;
+                continue;
+            }
+            if !is_ident(&tokens[i], "rule") && !is_ident(&tokens[i], "pub") {
+                if let Some((_, span)) = &expect {
+                    return Err(syn::Error::new(
+                        *span,
+                        "`#[expect]` must be placed on a rule or a pattern",
+                    ));
+                }
+                if let Some((_, span)) = &on_error {
+                    return Err(syn::Error::new(
+                        *span,
+                        "`#[on_error]` must be placed on a rule",
+                    ));
+                }
+            }
+
+            if is_ident(&tokens[i], "rule") {
+                let label = expect
+                    .take()
+                    .map(|(label, _)| label)
+                    .or_else(|| doc_label(&doc));
+                doc.clear();
+                if let (Some(label), Some(TokenTree::Ident(name))) = (label, tokens.get(i + 1)) {
+                    self.labels.push((name.to_string(), label));
+                }
+                if let (Some((build, span)), Some(TokenTree::Ident(name))) =
+                    (on_error.take(), tokens.get(i + 1))
+                {
+                    self.error_nodes.push((name.to_string(), build, span));
+                }
+                let eq = find_rule_eq(&tokens, i)?;
+                out.extend(tokens[i..=eq].iter().cloned());
+                let end = find_item_end(&tokens, eq + 1);
+                let mut bound = Vec::new();
+                let body = &tokens[eq + 1..end];
+                let start = out.len();
+                match self.lower_precedence(&tokens[i..eq], body, &mut bound)? {
+                    Some(body) => out.extend(body),
+                    None => out.extend(self.lower_patterns(body, true, &mut bound)?),
+                }
+                bodies.push((tokens[i + 1].to_string(), start..out.len()));
+                i = end;
+            } else if is_ident(&tokens[i], "use") {
+                doc.clear();
+                let end = find_semi(&tokens, i)?;
+                out.extend(tokens[i..=end].iter().cloned());
+                i = end + 1;
+            } else if is_option(&tokens, i) {
+                doc.clear();
+                let end = find_semi(&tokens, i)?;
+                self.set_option(&tokens[i], tokens[i + 2..end].iter().cloned().collect())?;
+                i = end + 1;
+            } else {
+                out.push(tokens[i].clone());
+                i += 1;
+            }
+        }
+
+        self.check_error_nodes()?;
+        self.hide_left_cycles(&mut out, &bodies);
+        Ok(out.into_iter().collect())
+    }
+
+    /// Checks that every `recover_node` calls a rule with `#[on_error(...)]`
+    /// and that every such rule is recovered by a `recover_node`.
+    fn check_error_nodes(&self) -> syn::Result<()> {
+        for hook in &self.hooks {
+            if let Hook::RecoverNode(rule) = hook {
+                if self.on_error(rule).is_none() {
+                    return Err(syn::Error::new(
+                        rule.span(),
+                        format!("`recover_node` needs `#[on_error(...)]` on the rule `{rule}`"),
+                    ));
+                }
+            }
+        }
+        for (name, _, span) in &self.error_nodes {
+            let recovered = self
+                .hooks
+                .iter()
+                .any(|hook| matches!(hook, Hook::RecoverNode(rule) if rule == name));
+            if !recovered {
+                return Err(syn::Error::new(
+                    *span,
+                    format!("`#[on_error]` has no effect: `{name}` is never recovered with `recover_node({name}, sync)`"),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Wraps each leading call that closes a cycle of leading calls between
+    /// different rules in a [`Hook::LeftCall`]. Direct left recursion is left
+    /// as it is.
+    fn hide_left_cycles(&mut self, out: &mut Vec<TokenTree>, bodies: &[(String, Range<usize>)]) {
+        // (caller, callee, index of the callee's name in `out`)
+        let mut calls = Vec::new();
+        for (caller, (_, body)) in bodies.iter().enumerate() {
+            for start in alternative_starts(&out[body.clone()]) {
+                let mut j = body.start + start;
+                if is_binding(out, j) {
+                    j += 2;
+                }
+                let callee = out[j..body.end].first().and_then(|t| match t {
+                    TokenTree::Ident(id) => bodies.iter().position(|(name, _)| id == name),
+                    _ => None,
+                });
+                if let Some(callee) = callee {
+                    calls.push((caller, callee, j));
+                }
+            }
+        }
+
+        // reaches[a][b]: `b` can be reached from `a` through leading calls.
+        let n = bodies.len();
+        let mut reaches = vec![vec![false; n]; n];
+        for &(caller, callee, _) in &calls {
+            reaches[caller][callee] = true;
+        }
+        close(&mut reaches);
+
+        // Rewrite from the back so the recorded indices stay valid.
+        let mut cyclic: Vec<usize> = calls
+            .into_iter()
+            .filter(|&(caller, callee, _)| caller != callee && reaches[callee][caller])
+            .map(|(_, _, j)| j)
+            .collect();
+        cyclic.sort_unstable();
+        for j in cyclic.into_iter().rev() {
+            let len = match out.get(j + 1) {
+                Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis => 2,
+                _ => 1,
+            };
+            let span = out[j].span();
+            let call: TokenStream = out.drain(j..j + len).collect();
+            let name = format_ident!("{}{}", HOOK_PREFIX, self.hooks.len(), span = span);
+            self.hooks.push(Hook::LeftCall);
+            let args = TokenTree::Group(Group::new(Delimiter::Parenthesis, call));
+            out.splice(j..j, [TokenTree::Ident(name), args]);
+        }
+    }
+
+    /// Lowers a `precedence(atom) { operators }` rule body to a single
+    /// alternative calling its hook with the atom. `None` for any other body.
+    /// `header` is the rule signature up to its `=`.
+    fn lower_precedence(
+        &mut self,
+        header: &[TokenTree],
+        tokens: &[TokenTree],
+        bound: &mut Vec<String>,
+    ) -> syn::Result<Option<Vec<TokenTree>>> {
+        let [TokenTree::Ident(kw), TokenTree::Group(atom), TokenTree::Group(table)] = tokens else {
+            return Ok(None);
+        };
+        if kw != "precedence"
+            || atom.delimiter() != Delimiter::Parenthesis
+            || table.delimiter() != Delimiter::Brace
+        {
+            return Ok(None);
+        }
+        if atom.stream().is_empty() {
+            return Err(syn::Error::new(
+                atom.span(),
+                "expected the atom pattern: `precedence(atom) { ... }`",
+            ));
+        }
+
+        let operators = parse_operators(table.stream())?;
+        let ty = return_type(header)?;
+        let inner: Vec<TokenTree> = atom.stream().into_iter().collect();
+        let lowered = self.lower_patterns(&inner, false, bound)?;
+        let name = format_ident!("{}{}", HOOK_PREFIX, self.hooks.len(), span = kw.span());
+        self.hooks.push(Hook::Precedence(operators, ty));
+
+        let mut group = Group::new(Delimiter::Parenthesis, lowered.into_iter().collect());
+        group.set_span(atom.span());
+        let value = format_ident!("value", span = Span::mixed_site());
+        Ok(Some(
+            quote!(#value: #name #group -> { #value })
+                .into_iter()
+                .collect(),
+        ))
+    }
+
+    /// Lowers a pattern token sequence. `top_level` is true for a rule body,
+    /// where alternatives end in `-> { action }` and guards are allowed.
+    /// `bound` collects the binding names seen so far in the rule.
+    fn lower_patterns(
+        &mut self,
+        tokens: &[TokenTree],
+        top_level: bool,
+        bound: &mut Vec<String>,
+    ) -> syn::Result<Vec<TokenTree>> {
+        let mut out = Vec::new();
+        let mut i = 0;
+        // A pending `#[expect]`: its description, span, and where the next pattern starts.
+        let mut expect: Option<(String, Span, usize)> = None;
+
+        while i < tokens.len() {
+            // Actions are Rust code and are copied verbatim.
+            if is_arrow(tokens, i) {
+                let end = (i + 3).min(tokens.len());
+                out.extend(tokens[i..end].iter().cloned());
+                i = end;
+                continue;
+            }
+
+            if let Some(attr) = attribute(tokens, i) {
+                let label = match syn::parse2::<syn::Meta>(attr.stream()) {
+                    Ok(syn::Meta::List(meta)) if meta.path.is_ident("expect") => {
+                        parse_expect(meta.tokens)?
+                    }
+                    _ => {
+                        return Err(syn::Error::new(
+                            attr.span(),
+                            "only `#[expect(\"...\")]` is supported on patterns",
+                        ))
+                    }
+                };
+                expect = Some((label, attr.span(), out.len()));
+                i += 2;
+                continue;
+            }
+
+            match &tokens[i] {
+                TokenTree::Ident(id) if id == "if" => {
+                    let arrow = (i + 1..tokens.len())
+                        .find(|&j| is_arrow(tokens, j))
+                        .filter(|_| top_level)
+                        .ok_or_else(|| {
+                            syn::Error::new(
+                                id.span(),
+                                "a guard must directly precede the action `->`",
+                            )
+                        })?;
+                    let cond: TokenStream = tokens[i + 1..arrow].iter().cloned().collect();
+                    if cond.is_empty() {
+                        return Err(syn::Error::new(
+                            id.span(),
+                            "expected a condition after `if`",
+                        ));
+                    }
+                    self.push_hook(Hook::Predicate(cond), id.span(), &mut out, None)?;
+                    i = arrow;
+                }
+                TokenTree::Punct(p) if p.as_char() == '&' => match tokens.get(i + 1) {
+                    Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => {
+                        self.push_hook(
+                            Hook::Predicate(g.stream()),
+                            p.span(),
+                            &mut out,
+                            tokens.get(i + 2),
+                        )?;
+                        i += 2;
+                    }
+                    _ => {
+                        out.push(tokens[i].clone());
+                        i += 1;
+                    }
+                },
+                TokenTree::Punct(p)
+                    if p.as_char() == '=' && p.spacing() == proc_macro2::Spacing::Alone =>
+                {
+                    match tokens.get(i + 1) {
+                        Some(TokenTree::Ident(name)) => {
+                            let hook = Hook::BackRef(name.clone());
+                            self.push_hook(hook, p.span(), &mut out, tokens.get(i + 2))?;
+                            i += 2;
+                        }
+                        _ => {
+                            return Err(syn::Error::new(
+                                p.span(),
+                                "expected a binding name after `=`",
+                            ))
+                        }
+                    }
+                }
+                TokenTree::Ident(id)
+                    if id == "take"
+                        && !self.rules.iter().any(|rule| id == rule)
+                        && matches!(tokens.get(i + 1), Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis) =>
+                {
+                    let TokenTree::Group(count) = &tokens[i + 1] else {
+                        unreachable!()
+                    };
+                    self.push_hook(
+                        Hook::Take(count.stream()),
+                        id.span(),
+                        &mut out,
+                        tokens.get(i + 2),
+                    )?;
+                    i += 2;
+                }
+                TokenTree::Ident(id)
+                    if id == "recover"
+                        && !self.rules.iter().any(|rule| id == rule)
+                        && matches!(tokens.get(i + 1), Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis) =>
+                {
+                    let TokenTree::Group(args) = &tokens[i + 1] else {
+                        unreachable!()
+                    };
+                    let inner: Vec<TokenTree> = args.stream().into_iter().collect();
+                    let mut lowered = self.lower_patterns(&inner, false, bound)?;
+                    self.default_sync(&mut lowered, args.span());
+                    let mut group = Group::new(Delimiter::Parenthesis, lowered.into_iter().collect());
+                    group.set_span(args.span());
+                    out.push(tokens[i].clone());
+                    out.push(TokenTree::Group(group));
+                    i += 2;
+                }
+                TokenTree::Ident(id)
+                    if id == "recover_node"
+                        && !self.rules.iter().any(|rule| id == rule)
+                        && matches!(tokens.get(i + 1), Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis) =>
+                {
+                    let TokenTree::Group(args) = &tokens[i + 1] else {
+                        unreachable!()
+                    };
+                    let inner: Vec<TokenTree> = args.stream().into_iter().collect();
+                    let mut lowered = self.lower_patterns(&inner, false, bound)?;
+                    self.default_sync(&mut lowered, args.span());
+                    // The first argument must be a plain call, with or
+                    // without arguments, of a rule of this grammar.
+                    let commas = lowered.iter().filter(|t| is_punct(t, ',')).count();
+                    let rule = match lowered.as_slice() {
+                        [TokenTree::Ident(rule), TokenTree::Punct(p), ..] if p.as_char() == ',' => {
+                            Some(rule)
+                        }
+                        [TokenTree::Ident(rule), TokenTree::Group(g), TokenTree::Punct(p), ..]
+                            if g.delimiter() == Delimiter::Parenthesis && p.as_char() == ',' =>
+                        {
+                            Some(rule)
+                        }
+                        _ => None,
+                    };
+                    let rule = match rule {
+                        Some(rule) if commas == 1 && self.rules.iter().any(|r| rule == r) => {
+                            rule.clone()
+                        }
+                        _ => {
+                            return Err(syn::Error::new(
+                                args.span(),
+                                "expected `recover_node(rule, sync)` or `recover_node(rule)`, where `rule` is a rule of this grammar",
+                            ))
+                        }
+                    };
+                    let name = format_ident!("{}{}", HOOK_PREFIX, self.hooks.len(), span = id.span());
+                    self.hooks.push(Hook::RecoverNode(rule));
+                    let mut group = Group::new(Delimiter::Parenthesis, lowered.into_iter().collect());
+                    group.set_span(args.span());
+                    out.push(TokenTree::Ident(name));
+                    out.push(TokenTree::Group(group));
+                    i += 2;
+                }
+                TokenTree::Ident(id)
+                    if id == "try_recover"
+                        && !self.rules.iter().any(|rule| id == rule)
+                        && matches!(tokens.get(i + 1), Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis) =>
+                {
+                    let TokenTree::Group(args) = &tokens[i + 1] else {
+                        unreachable!()
+                    };
+                    let inner: Vec<TokenTree> = args.stream().into_iter().collect();
+                    let mut lowered = self.lower_patterns(&inner, false, bound)?;
+                    self.default_sync(&mut lowered, args.span());
+                    let commas = lowered.iter().filter(|t| is_punct(t, ',')).count();
+                    if commas != 1 {
+                        return Err(syn::Error::new(
+                            args.span(),
+                            "expected `try_recover(rule, sync)` or `try_recover(rule)`",
+                        ));
+                    }
+                    let name = format_ident!("{}{}", HOOK_PREFIX, self.hooks.len(), span = id.span());
+                    self.hooks.push(Hook::TryRecover);
+                    let mut group = Group::new(Delimiter::Parenthesis, lowered.into_iter().collect());
+                    group.set_span(args.span());
+                    out.push(TokenTree::Ident(name));
+                    out.push(TokenTree::Group(group));
+                    i += 2;
+                }
+                TokenTree::Ident(id) if id == "in" && preceding_atom_len(&out) > 0 => {
+                    let end = range_end(tokens, i + 1, bound);
+                    let range: TokenStream = tokens[i + 1..end].iter().cloned().collect();
+                    if !matches!(syn::parse2(range.clone()), Ok(syn::Expr::Range(_))) {
+                        return Err(syn::Error::new(
+                            id.span(),
+                            "expected a range after `in`, such as `0..=59`",
+                        ));
+                    }
+                    self.push_wrapping_hook(Hook::InRange(range), id.span(), &mut out);
+                    i = end;
+                }
+                TokenTree::Ident(id)
+                    if id == "int_literal" && !self.rules.iter().any(|rule| id == rule) =>
+                {
+                    let (ty, end) = parse_turbofish(tokens, i + 1).ok_or_else(|| {
+                        syn::Error::new(
+                            id.span(),
+                            "expected an integer type: `int_literal::<T>`",
+                        )
+                    })?;
+                    self.push_hook(
+                        Hook::IntLiteral(syn::parse2(ty)?),
+                        id.span(),
+                        &mut out,
+                        tokens.get(end),
+                    )?;
+                    i = end;
+                }
+                TokenTree::Ident(id)
+                    if id == "bits"
+                        && !self.rules.iter().any(|rule| id == rule)
+                        && matches!(tokens.get(i + 1), Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace) =>
+                {
+                    let TokenTree::Group(body) = &tokens[i + 1] else {
+                        unreachable!()
+                    };
+                    let fields = parse_bit_fields(id, body.stream())?;
+                    bound.extend(fields.iter().flat_map(|f| &f.name).map(|n| n.to_string()));
+                    self.push_hook(
+                        Hook::Bits(fields, id.span()),
+                        id.span(),
+                        &mut out,
+                        tokens.get(i + 2),
+                    )?;
+                    i += 2;
+                }
+                TokenTree::Ident(id)
+                    if tokens.get(i + 1).is_some_and(|t| {
+                        matches!(t, TokenTree::Punct(p) if p.as_char() == ':' && p.spacing() == proc_macro2::Spacing::Alone)
+                    }) =>
+                {
+                    bound.push(id.to_string());
+                    out.push(tokens[i].clone());
+                    i += 1;
+                }
+                TokenTree::Group(g)
+                    if g.delimiter() == Delimiter::Brace
+                        && preceding_atom_len(&out) > 0
+                        && is_count(g, bound) =>
+                {
+                    self.push_wrapping_hook(Hook::Count(g.stream()), g.span(), &mut out);
+                    i += 1;
+                }
+                TokenTree::Group(g) => {
+                    let inner: Vec<TokenTree> = g.stream().into_iter().collect();
+                    let lowered = self.lower_patterns(&inner, false, bound)?;
+                    let mut group = Group::new(g.delimiter(), lowered.into_iter().collect());
+                    group.set_span(g.span());
+                    out.push(TokenTree::Group(group));
+                    i += 1;
+                }
+                other => {
+                    out.push(other.clone());
+                    i += 1;
+                }
+            }
+
+            // Wrap the pattern after `#[expect]` once it is complete: a
+            // binding's `name:` and a call's arguments are still to come.
+            if let Some((_, _, mark)) = &expect {
+                let atom = preceding_atom_len(&out);
+                let start = out.len() - atom;
+                let incomplete = tokens.get(i).is_some_and(|t| {
+                    is_punct(t, ':')
+                        || matches!(t, TokenTree::Group(g) if g.delimiter() == Delimiter::Parenthesis)
+                });
+                if atom > 0 && (start == *mark || start == *mark + 2) && !incomplete {
+                    let (label, span, _) = expect.take().unwrap();
+                    self.push_wrapping_hook(Hook::Expect(label), span, &mut out);
+                }
+            }
+        }
+
+        if let Some((_, span, _)) = expect {
+            return Err(syn::Error::new(
+                span,
+                "expected a pattern after `#[expect]`",
+            ));
+        }
+
+        Ok(out)
+    }
+}
+
+fn is_ident(tt: &TokenTree, name: &str) -> bool {
+    matches!(tt, TokenTree::Ident(id) if id == name)
+}
+
+fn is_punct(tt: &TokenTree, ch: char) -> bool {
+    matches!(tt, TokenTree::Punct(p) if p.as_char() == ch)
+}
+
+/// Extends a reachability matrix to its transitive closure.
+pub(crate) fn close(reaches: &mut [Vec<bool>]) {
+    for k in 0..reaches.len() {
+        let via = reaches[k].clone();
+        for row in reaches.iter_mut().filter(|row| row[k]) {
+            for (reached, &next) in row.iter_mut().zip(&via) {
+                *reached |= next;
+            }
+        }
+    }
+}
+
+/// `name:` at `tokens[i]`, binding the pattern that follows.
+fn is_binding(tokens: &[TokenTree], i: usize) -> bool {
+    matches!(tokens.get(i), Some(TokenTree::Ident(_)))
+        && matches!(tokens.get(i + 1), Some(TokenTree::Punct(p)) if p.as_char() == ':' && p.spacing() == proc_macro2::Spacing::Alone)
+}
+
+/// Where each alternative of a lowered rule body starts: at the beginning,
+/// and after each `|` that follows an action.
+fn alternative_starts(body: &[TokenTree]) -> Vec<usize> {
+    let mut starts = vec![0];
+    for i in 3..body.len() {
+        if is_punct(&body[i], '|')
+            && matches!(&body[i - 1], TokenTree::Group(g) if g.delimiter() == Delimiter::Brace)
+            && is_arrow(body, i - 3)
+        {
+            starts.push(i + 1);
+        }
+    }
+    starts.retain(|&start| start < body.len());
+    starts
+}
+
+/// `->` at `tokens[i]`.
+fn is_arrow(tokens: &[TokenTree], i: usize) -> bool {
+    matches!(&tokens[i], TokenTree::Punct(p) if p.as_char() == '-' && p.spacing() == proc_macro2::Spacing::Joint)
+        && tokens.get(i + 1).is_some_and(|t| is_punct(t, '>'))
+}
+
+/// Finds the `=` separating a rule header from its body. Generic arguments in
+/// the return type (`impl Iterator<Item = T>`) are skipped.
+fn find_rule_eq(tokens: &[TokenTree], start: usize) -> syn::Result<usize> {
+    let mut depth = 0usize;
+    for i in start..tokens.len() {
+        match &tokens[i] {
+            TokenTree::Punct(p) if p.as_char() == '<' => depth += 1,
+            TokenTree::Punct(p) if p.as_char() == '>' && !is_arrow(tokens, i.saturating_sub(1)) => {
+                depth = depth.saturating_sub(1)
+            }
+            TokenTree::Punct(p) if p.as_char() == '=' && depth == 0 => return Ok(i),
+            _ => {}
+        }
+    }
+    let span = tokens[start].span();
+    Err(syn::Error::new(span, "expected `=` after rule signature"))
+}
+
+/// Length of the pattern atom at the end of `out`: a name, a literal, a
+/// group, or a call such as `rule(args)`. Zero if `out` does not end in one.
+fn preceding_atom_len(out: &[TokenTree]) -> usize {
+    match out {
+        [.., TokenTree::Ident(_), TokenTree::Group(g)]
+            if g.delimiter() == Delimiter::Parenthesis =>
+        {
+            2
+        }
+        [.., TokenTree::Group(g)] if g.delimiter() != Delimiter::Brace => 1,
+        [.., TokenTree::Ident(_)] | [.., TokenTree::Literal(_)] => 1,
+        _ => 0,
+    }
+}
+
+/// `{ ... }` after a pattern is a repetition count if it holds integer
+/// literals and earlier bindings combined with arithmetic. Anything else is
+/// a braced pattern.
+fn is_count(group: &Group, bound: &[String]) -> bool {
+    let tokens: Vec<TokenTree> = group.stream().into_iter().collect();
+    !tokens.is_empty()
+        && tokens.iter().all(|t| match t {
+            TokenTree::Literal(lit) => syn::parse_str::<syn::LitInt>(&lit.to_string()).is_ok(),
+            TokenTree::Ident(id) => bound.contains(&id.to_string()),
+            TokenTree::Punct(p) => "+-*/%".contains(p.as_char()),
+            TokenTree::Group(g) => g.delimiter() == Delimiter::Parenthesis && is_count(g, bound),
+        })
+}
+
+/// Finds the end of the range starting at `tokens[i]`. Bounds are numbers,
+/// earlier bindings or parenthesized expressions, so the next pattern (or the
+/// action's `->`) ends the range.
+fn range_end(tokens: &[TokenTree], i: usize, bound: &[String]) -> usize {
+    let mut j = i;
+    while let Some(tt) = tokens.get(j) {
+        let part = match tt {
+            TokenTree::Literal(lit) => lit.to_string().starts_with(|c: char| c.is_ascii_digit()),
+            TokenTree::Ident(id) => bound.contains(&id.to_string()),
+            TokenTree::Group(g) => g.delimiter() == Delimiter::Parenthesis,
+            TokenTree::Punct(p) => match p.as_char() {
+                '.' => true,
+                // `..=`, but not a back-reference `=name`
+                '=' => j > i && is_punct(&tokens[j - 1], '.'),
+                '-' => !is_arrow(tokens, j),
+                _ => false,
+            },
+        };
+        if !part {
+            break;
+        }
+        j += 1;
+    }
+    j
+}
+
+/// Reads `::<T>` at `tokens[i]`, returning `T` and the index after the closing `>`.
+fn parse_turbofish(tokens: &[TokenTree], i: usize) -> Option<(TokenStream, usize)> {
+    let colons = tokens.get(i..i + 3)?;
+    if !(is_punct(&colons[0], ':') && is_punct(&colons[1], ':') && is_punct(&colons[2], '<')) {
+        return None;
+    }
+    let mut depth = 1;
+    for (j, tt) in tokens.iter().enumerate().skip(i + 3) {
+        if is_punct(tt, '<') {
+            depth += 1;
+        } else if is_punct(tt, '>') {
+            depth -= 1;
+            if depth == 0 {
+                return Some((tokens[i + 3..j].iter().cloned().collect(), j + 1));
+            }
+        }
+    }
+    None
+}
+
+/// Parses the fields of a `bits { ... }` block, which must cover whole bytes.
+// `usize::is_multiple_of` needs Rust 1.87.
+#[allow(clippy::manual_is_multiple_of)]
+fn parse_bit_fields(kw: &proc_macro2::Ident, body: TokenStream) -> syn::Result<Vec<BitField>> {
+    use syn::parse::{ParseStream, Parser};
+
+    let parse = |input: ParseStream| -> syn::Result<Vec<BitField>> {
+        let mut fields = Vec::new();
+        while !input.is_empty() {
+            let name = if input.peek(syn::Token![_]) {
+                input.parse::<syn::Token![_]>()?;
+                input.parse::<syn::Token![:]>()?;
+                None
+            } else if input.peek2(syn::Token![:]) {
+                let name: syn::Ident = input.parse()?;
+                input.parse::<syn::Token![:]>()?;
+                Some(name)
+            } else {
+                None
+            };
+            let ty: syn::Ident = input.parse()?;
+            let width = ty
+                .to_string()
+                .strip_prefix('u')
+                .and_then(|w| w.parse::<usize>().ok())
+                .filter(|w| (1..=128).contains(w))
+                .ok_or_else(|| {
+                    syn::Error::new(ty.span(), "expected a bit width from `u1` to `u128`")
+                })?;
+            fields.push(BitField { name, width });
+        }
+        Ok(fields)
+    };
+
+    let fields = parse.parse2(body)?;
+    let total: usize = fields.iter().map(|f| f.width).sum();
+    if total == 0 || total % 8 != 0 {
+        return Err(syn::Error::new(
+            kw.span(),
+            format!(
+                "`bits` block covers {} bits; it must end on a byte boundary (a multiple of 8)",
+                total
+            ),
+        ));
+    }
+    Ok(fields)
+}
+
+/// The return type in a rule signature: the tokens between `->` and a
+/// `where` clause or the end.
+fn return_type(header: &[TokenTree]) -> syn::Result<syn::Type> {
+    let arrow = (0..header.len())
+        .find(|&i| is_arrow(header, i))
+        .ok_or_else(|| syn::Error::new(header[0].span(), "expected `->` and a return type"))?;
+    let end = (arrow + 2..header.len())
+        .find(|&i| is_ident(&header[i], "where"))
+        .unwrap_or(header.len());
+    syn::parse2(header[arrow + 2..end].iter().cloned().collect())
+}
+
+/// Parses the operator table of a `precedence` rule. Each entry is a fixity,
+/// a level and the operator written as it appears with named operands.
+fn parse_operators(body: TokenStream) -> syn::Result<Vec<Operator>> {
+    use syn::parse::{ParseStream, Parser};
+
+    let parse = |input: ParseStream| -> syn::Result<Vec<Operator>> {
+        let mut operators = Vec::new();
+        while !input.is_empty() {
+            let kw: syn::Ident = input.parse()?;
+            let (fixity, shape) =
+                match kw.to_string().as_str() {
+                    "prefix" => (Fixity::Prefix, "\"-\" x"),
+                    "postfix" => (Fixity::Postfix, "x \"!\""),
+                    "left" => (Fixity::Left, "l \"+\" r"),
+                    "right" => (Fixity::Right, "l \"^\" r"),
+                    "nonassoc" => (Fixity::NonAssoc, "l \"==\" r"),
+                    "ternary" => (Fixity::Ternary, "c \"?\" a \":\" b"),
+                    _ => return Err(syn::Error::new(
+                        kw.span(),
+                        "expected `prefix`, `postfix`, `left`, `right`, `nonassoc` or `ternary`",
+                    )),
+                };
+            let level: syn::LitInt = input.parse()?;
+            let level = level.base10_parse::<u16>()?.into();
+            input.parse::<syn::Token![:]>()?;
+
+            // The operator as written: operand names around the literals.
+            let mut layout = String::new();
+            let mut literals = Vec::new();
+            let mut operands = Vec::new();
+            while !input.is_empty() && !input.peek(syn::Token![->]) {
+                if input.peek(syn::Lit) {
+                    literals.push(input.parse()?);
+                    layout.push('l');
+                } else if input.peek(syn::Token![_]) {
+                    let underscore: syn::Token![_] = input.parse()?;
+                    operands.push(syn::Ident::new("_", underscore.span));
+                    layout.push('o');
+                } else {
+                    operands.push(input.parse()?);
+                    layout.push('o');
+                }
+            }
+            let expected = match fixity {
+                Fixity::Prefix => "lo",
+                Fixity::Postfix => "ol",
+                Fixity::Ternary => "ololo",
+                _ => "olo",
+            };
+            if layout != expected {
+                return Err(syn::Error::new(
+                    kw.span(),
+                    format!("a `{}` operator is written like `{}`", kw, shape),
+                ));
+            }
+            input.parse::<syn::Token![->]>()?;
+            let action: Group = input.parse()?;
+            if action.delimiter() != Delimiter::Brace {
+                return Err(syn::Error::new(
+                    action.span(),
+                    "expected an action `{ ... }`",
+                ));
+            }
+            operators.push(Operator {
+                fixity,
+                level,
+                literals,
+                operands,
+                action: action.stream(),
+            });
+        }
+        Ok(operators)
+    };
+
+    parse.parse2(body)
+}
+
+/// A grammar-level option, `key: value;` or `key = value;`.
+fn is_option(tokens: &[TokenTree], i: usize) -> bool {
+    ["state", "error", "input", "recovery", "repair"]
+        .iter()
+        .any(|key| is_ident(&tokens[i], key))
+        && tokens
+            .get(i + 1)
+            .is_some_and(|t| is_punct(t, ':') || is_punct(t, '='))
+}
+
+/// Finds the `;` terminating the item that starts at `start`.
+fn find_semi(tokens: &[TokenTree], start: usize) -> syn::Result<usize> {
+    (start..tokens.len())
+        .find(|&i| is_punct(&tokens[i], ';'))
+        .ok_or_else(|| syn::Error::new(tokens[start].span(), "expected `;`"))
+}
+
+/// Finds where the next grammar item (`rule`, `pub rule`, `use`, attribute) starts.
+fn find_item_end(tokens: &[TokenTree], start: usize) -> usize {
+    (start..tokens.len())
+        .find(|&i| {
+            is_ident(&tokens[i], "rule")
+                || is_ident(&tokens[i], "pub")
+                || is_ident(&tokens[i], "use")
+                || is_rule_attribute(tokens, i)
+        })
+        .unwrap_or(tokens.len())
+}
+
+/// The `[...]` group of an attribute `#[...]` starting at `tokens[i]`.
+fn attribute(tokens: &[TokenTree], i: usize) -> Option<&Group> {
+    if !is_punct(&tokens[i], '#') {
+        return None;
+    }
+    match tokens.get(i + 1) {
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Bracket => Some(g),
+        _ => None,
+    }
+}
+
+/// An attribute at `tokens[i]` that belongs to the following rule rather
+/// than to a pattern of the current one.
+fn is_rule_attribute(tokens: &[TokenTree], mut i: usize) -> bool {
+    if attribute(tokens, i).is_none() {
+        return false;
+    }
+    while attribute(tokens, i).is_some() {
+        i += 2;
+    }
+    tokens
+        .get(i)
+        .is_some_and(|t| is_ident(t, "pub") || is_ident(t, "rule"))
+}
+
+/// The attributes this backend reads on rules.
+enum RuleAttribute {
+    /// `#[expect("a cron field")]`
+    Expect(String),
+    /// `#[on_error(|span, text| Expr::Error(span))]`
+    OnError(syn::Expr),
+    /// One line of a doc comment.
+    Doc(String),
+}
+
+impl RuleAttribute {
+    /// `None` for attributes that are passed through unchanged.
+    fn parse(attr: &Group) -> syn::Result<Option<Self>> {
+        match syn::parse2::<syn::Meta>(attr.stream())? {
+            syn::Meta::NameValue(meta) if meta.path.is_ident("doc") => match meta.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(line),
+                    ..
+                }) => Ok(Some(RuleAttribute::Doc(line.value()))),
+                _ => Ok(None),
+            },
+            syn::Meta::List(meta) if meta.path.is_ident("expect") => {
+                Ok(Some(RuleAttribute::Expect(parse_expect(meta.tokens)?)))
+            }
+            syn::Meta::List(meta) if meta.path.is_ident("on_error") => {
+                let build = syn::parse2(meta.tokens).map_err(|e| {
+                    syn::Error::new(
+                        e.span(),
+                        "expected a function: `#[on_error(|span, text| ...)]`",
+                    )
+                })?;
+                Ok(Some(RuleAttribute::OnError(build)))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The description inside `#[expect("...")]`.
+fn parse_expect(tokens: TokenStream) -> syn::Result<String> {
+    let label: syn::LitStr = syn::parse2(tokens)
+        .map_err(|e| syn::Error::new(e.span(), "expected a description: `#[expect(\"...\")]`"))?;
+    if label.value().trim().is_empty() {
+        return Err(syn::Error::new(
+            label.span(),
+            "the description must not be empty",
+        ));
+    }
+    Ok(label.value())
+}
+
+/// A rule description taken from its doc comment: the first paragraph on
+/// one line, without a trailing period, starting in lower case unless it
+/// begins with an acronym.
+fn doc_label(doc: &[String]) -> Option<String> {
+    let paragraph: Vec<&str> = doc
+        .iter()
+        .map(|line| line.trim())
+        .skip_while(|line| line.is_empty())
+        .take_while(|line| !line.is_empty())
+        .collect();
+    let label = paragraph.join(" ");
+    let label = label.strip_suffix('.').unwrap_or(&label);
+    let mut chars = label.chars();
+    let first = chars.next()?;
+    let rest = chars.as_str();
+    if rest.starts_with(char::is_uppercase) {
+        return Some(label.to_string());
+    }
+    Some(first.to_lowercase().chain(rest.chars()).collect())
+}
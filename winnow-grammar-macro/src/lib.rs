@@ -8,6 +8,7 @@ use syn_grammar_model::Backend;
 use syn_grammar_model::BuiltIn;
 
 mod codegen;
+mod extensions;
 
 struct WinnowBackend;
 
@@ -151,6 +152,237 @@ impl Backend for WinnowBackend {
                 name: "bool",
                 return_type: "bool",
             },
+            // Radix integers (`0x`/`0o`/`0b` prefix optional)
+            BuiltIn {
+                name: "hex_u8",
+                return_type: "u8",
+            },
+            BuiltIn {
+                name: "hex_u16",
+                return_type: "u16",
+            },
+            BuiltIn {
+                name: "hex_u32",
+                return_type: "u32",
+            },
+            BuiltIn {
+                name: "hex_u64",
+                return_type: "u64",
+            },
+            BuiltIn {
+                name: "hex_u128",
+                return_type: "u128",
+            },
+            BuiltIn {
+                name: "hex_usize",
+                return_type: "usize",
+            },
+            BuiltIn {
+                name: "oct_u8",
+                return_type: "u8",
+            },
+            BuiltIn {
+                name: "oct_u16",
+                return_type: "u16",
+            },
+            BuiltIn {
+                name: "oct_u32",
+                return_type: "u32",
+            },
+            BuiltIn {
+                name: "oct_u64",
+                return_type: "u64",
+            },
+            BuiltIn {
+                name: "oct_u128",
+                return_type: "u128",
+            },
+            BuiltIn {
+                name: "oct_usize",
+                return_type: "usize",
+            },
+            BuiltIn {
+                name: "bin_u8",
+                return_type: "u8",
+            },
+            BuiltIn {
+                name: "bin_u16",
+                return_type: "u16",
+            },
+            BuiltIn {
+                name: "bin_u32",
+                return_type: "u32",
+            },
+            BuiltIn {
+                name: "bin_u64",
+                return_type: "u64",
+            },
+            BuiltIn {
+                name: "bin_u128",
+                return_type: "u128",
+            },
+            BuiltIn {
+                name: "bin_usize",
+                return_type: "usize",
+            },
+            // Float dialects
+            BuiltIn {
+                name: "json_f32",
+                return_type: "f32",
+            },
+            BuiltIn {
+                name: "json_f64",
+                return_type: "f64",
+            },
+            BuiltIn {
+                name: "toml_f32",
+                return_type: "f32",
+            },
+            BuiltIn {
+                name: "toml_f64",
+                return_type: "f64",
+            },
+            BuiltIn {
+                name: "rust_f32",
+                return_type: "f32",
+            },
+            BuiltIn {
+                name: "rust_f64",
+                return_type: "f64",
+            },
+            BuiltIn {
+                name: "hex_f32",
+                return_type: "f32",
+            },
+            BuiltIn {
+                name: "hex_f64",
+                return_type: "f64",
+            },
+            BuiltIn {
+                name: "json_decimal",
+                return_type: "String",
+            },
+            // Binary numbers (byte input only)
+            BuiltIn {
+                name: "be_u8",
+                return_type: "u8",
+            },
+            BuiltIn {
+                name: "be_u16",
+                return_type: "u16",
+            },
+            BuiltIn {
+                name: "be_u24",
+                return_type: "u32",
+            },
+            BuiltIn {
+                name: "be_u32",
+                return_type: "u32",
+            },
+            BuiltIn {
+                name: "be_u64",
+                return_type: "u64",
+            },
+            BuiltIn {
+                name: "be_u128",
+                return_type: "u128",
+            },
+            BuiltIn {
+                name: "be_i8",
+                return_type: "i8",
+            },
+            BuiltIn {
+                name: "be_i16",
+                return_type: "i16",
+            },
+            BuiltIn {
+                name: "be_i24",
+                return_type: "i32",
+            },
+            BuiltIn {
+                name: "be_i32",
+                return_type: "i32",
+            },
+            BuiltIn {
+                name: "be_i64",
+                return_type: "i64",
+            },
+            BuiltIn {
+                name: "be_i128",
+                return_type: "i128",
+            },
+            BuiltIn {
+                name: "be_f32",
+                return_type: "f32",
+            },
+            BuiltIn {
+                name: "be_f64",
+                return_type: "f64",
+            },
+            BuiltIn {
+                name: "le_u8",
+                return_type: "u8",
+            },
+            BuiltIn {
+                name: "le_u16",
+                return_type: "u16",
+            },
+            BuiltIn {
+                name: "le_u24",
+                return_type: "u32",
+            },
+            BuiltIn {
+                name: "le_u32",
+                return_type: "u32",
+            },
+            BuiltIn {
+                name: "le_u64",
+                return_type: "u64",
+            },
+            BuiltIn {
+                name: "le_u128",
+                return_type: "u128",
+            },
+            BuiltIn {
+                name: "le_i8",
+                return_type: "i8",
+            },
+            BuiltIn {
+                name: "le_i16",
+                return_type: "i16",
+            },
+            BuiltIn {
+                name: "le_i24",
+                return_type: "i32",
+            },
+            BuiltIn {
+                name: "le_i32",
+                return_type: "i32",
+            },
+            BuiltIn {
+                name: "le_i64",
+                return_type: "i64",
+            },
+            BuiltIn {
+                name: "le_i128",
+                return_type: "i128",
+            },
+            BuiltIn {
+                name: "le_f32",
+                return_type: "f32",
+            },
+            BuiltIn {
+                name: "le_f64",
+                return_type: "f64",
+            },
+            BuiltIn {
+                name: "uleb128",
+                return_type: "u64",
+            },
+            BuiltIn {
+                name: "sleb128",
+                return_type: "i64",
+            },
         ]
     }
 }
@@ -161,15 +393,21 @@ pub fn grammar(input: TokenStream) -> TokenStream {
 }
 
 fn grammar_impl(input: TokenStream) -> TokenStream {
-    // 1. Parse & Validate using syn-grammar-model with specific built-ins
+    // 1. Lower backend-specific syntax extensions into plain rule calls
+    let (input, ext) = match extensions::lower(input.into()) {
+        Ok(lowered) => lowered,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    // 2. Parse & Validate using syn-grammar-model with specific built-ins
     // Note: validator is patched in vendored source to support typed generic params.
-    let m_ast = match parse_grammar::<WinnowBackend>(input.into()) {
+    let m_ast = match parse_grammar::<WinnowBackend>(input) {
         Ok(ast) => ast,
         Err(e) => return e.to_compile_error().into(),
     };
 
-    // 2. Generate Code using local winnow codegen
-    match codegen::generate_rust(m_ast) {
+    // 3. Generate Code using local winnow codegen
+    match codegen::generate_rust(m_ast, &ext) {
         Ok(stream) => stream.into(),
         Err(e) => e.to_compile_error().into(),
     }